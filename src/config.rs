@@ -1,3 +1,4 @@
+use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 
 use crate::errors::ValidationError;
@@ -11,30 +12,166 @@ pub enum CastType {
     Bool,
     Int,
     Float,
+    /// parses the string as JSON into a structured value, an invalid JSON becomes `null`.
+    Json,
+    /// splits the string by commas into an array of integers, elements that are not integers are skipped.
+    #[serde(rename = "int_array")]
+    IntArray,
+    /// splits the string by commas into an array of floats, elements that are not numbers are skipped.
+    #[serde(rename = "float_array")]
+    FloatArray,
 }
+/// `PageField` is an enum that represents common page-level fields, which can be extracted without
+/// specifying `base_path` and `extract` options.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PageField {
+    /// the text of the `<title>` element.
+    Title,
+    /// the `href` of the `<link rel="canonical">` element.
+    Canonical,
+    /// the `content` of the `<meta name="description">` element.
+    Description,
+    /// the `href` of the `<link rel="icon">` (or `rel="shortcut icon"`) element.
+    Favicon,
+}
+
+impl PageField {
+    /// Returns the selector's path to the element of the field.
+    pub fn base_path(&self) -> &'static str {
+        match self {
+            PageField::Title => "head title",
+            PageField::Canonical => r#"head link[rel="canonical"][href]"#,
+            PageField::Description => r#"head meta[name="description"][content]"#,
+            PageField::Favicon => r#"head link[rel~="icon"][href]"#,
+        }
+    }
+
+    /// Returns what needs to be extracted from the element of the field.
+    pub fn extract(&self) -> &'static str {
+        match self {
+            PageField::Title => "text",
+            PageField::Canonical | PageField::Favicon => "href",
+            PageField::Description => "content",
+        }
+    }
+}
+
+/// `InputMode` is an enum that represents how the `Finder` builds the document from the html.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputMode {
+    /// the html is parsed as a whole document, missing `<html>`, `<head>` and `<body>` elements are added.
+    #[default]
+    Document,
+    /// the html is parsed as a fragment (i.e. a bare `<ul>`), the top-level nodes are the children of the `<html>` element
+    /// and no `<head>` or `<body>` elements are added.
+    Fragment,
+}
+
 /// `Config` is a struct that represents the configuration of the `Finder`.
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Config {
     /// represents a key for result the and every inline element if it is presented.  
     pub name: String,
+    /// represents how the html is parsed: `document` (default) or `fragment`.
+    /// It is used only by the root config, it is ignored in descendant configs.
+    #[serde(default)]
+    pub input_mode: InputMode,
     /// is a selector's path to the element to handle. May be omitted if the `inherit` option is set to `true`.
     #[serde(default)]
     pub base_path: String,
-    /// represents what finder needs to extract. Accepted values are `text`, `inner_text`, `html`, `inner_html`, `immediate_text` or an html-attribute name.
+    /// is a pair of an html-attribute name and a regex pattern.
+    /// If it is set, then only nodes with the attribute matching the pattern will be kept after `base_path` selection.
+    #[serde(default)]
+    pub filter: Option<(String, String)>,
+    /// if it is `true` then finder will skip matched nodes that are hidden, i.e. the node or one of its ancestors
+    /// has `display: none` or `visibility: hidden` in the inline `style` attribute.
+    /// This is a best-effort check: styles from `<style>` elements and stylesheets are not taken into account.
+    #[serde(default)]
+    pub skip_hidden: bool,
+    /// if it is `true` and `base_path` matches nothing, then finder retries the selection with class and id selectors
+    /// compared case-insensitively, i.e. `.Result` matches `<div class="result">`.
+    /// It is a fallback for inconsistent casing, not a full CSS override: if the original path matches any node,
+    /// the result is not extended with case-insensitive matches.
+    #[serde(default)]
+    pub ci_class: bool,
+    /// is an ARIA role, i.e. `button`. If it is set, then only nodes with the matching explicit (`role` attribute)
+    /// or implicit role (i.e. `<button>` has the `button` role) will be kept after `base_path` selection.
+    /// Implicit roles are known only for common elements, the check is case-insensitive.
+    #[serde(default)]
+    pub role: Option<String>,
+    /// is a shortcut for common page-level fields. Accepted values are `title`, `canonical`, `description` and `favicon`.
+    /// It sets the corresponding `base_path` and `extract`, so they must be omitted.
+    /// The path is relative to the parent's selection, so the parent must contain the `<head>` element (i.e. `html`).
+    #[serde(default)]
+    pub page: Option<PageField>,
+    /// represents what finder needs to extract. Accepted values are `text`, `inner_text`, `html`, `inner_html`, `immediate_text`, `comment`, `accessible_name`, `sibling_index`, `tag_name`, `attrs`, `data`, `class_list`, `count` or an html-attribute name.
+    /// `accessible_name` is the first non-empty of: `aria-label`, the text of the elements referenced by `aria-labelledby`,
+    /// `title` and the text content. `sibling_index` is the 0-based index of the element among its element siblings.
+    /// `tag_name` is the local name of the element, i.e. `a` or `article`.
+    /// `attrs` is an object with all attributes of the element (name -> value), it can not be combined with the `pipeline` option.
+    /// `data` is the same, but only for the `data-*` attributes, the keys are without the `data-` prefix (i.e. `data-item-id` gives `item-id`).
+    /// An attribute name with the `[]` suffix, i.e. `class[]` or `rel[]`, gives an array of the whitespace-separated tokens
+    /// of the attribute, it can not be combined with the `pipeline` option either.
+    /// `class_list` is like `class[]`, but it gives an empty array if the element has no `class` attribute.
+    /// `count` gives the number of the matched elements as an integer instead of an array, it requires the `many` option.
+    /// Unlike other zero values, a zero count is kept in the result object.
+    /// It can not be combined with the `pipeline` option, the `unique` and `reverse` options have no effect on it.
+    /// An attribute, which name is a reserved word, can be extracted with the `attr:` prefix, i.e. `attr:text`.
     #[serde(default)]
     pub extract: String,
-    /// is a type of the result value. Accepted values are `bool`, `int`, `float` or `string` (default, and should be omitted).
+    /// if it is `true` then html entities are decoded in extracted attribute values (like the `html_unescape` procedure does)
+    /// before the `pipeline` is applied. It has no effect on other extract types, i.e. `text` is already decoded.
+    #[serde(default)]
+    pub decode_entities: bool,
+    /// if it is `true` then the leading and trailing whitespace is trimmed from the extracted `html` or `inner_html`
+    /// before the `pipeline` is applied. The whitespace inside the html is kept. It has no effect on other extract types.
+    #[serde(default)]
+    pub trim_html: bool,
+    /// is a type of the result value. Accepted values are `bool`, `int`, `float`, `json`, `int_array`, `float_array` or `string` (default, and should be omitted).
     #[serde(default)]
     pub cast: CastType,
+    /// if it is `true` then a value that can not be casted to `int` or `float` becomes `null`.
+    /// Otherwise (by default) it becomes `0`, which can not be distinguished from a real zero.
+    #[serde(default)]
+    pub strict_cast: bool,
+    /// is a value that will be used if the final result (after `pipeline` and `cast`) is `null` or empty.
+    /// It is casted to the `cast` type as well.
+    #[serde(default)]
+    pub default_value: Option<String>,
+    /// if it is `true` then a missing selection is reported by `Finder::try_parse` as an error,
+    /// other parsing methods ignore it. The selection of a descendant config is looked up only if its parent's selection exists,
+    /// so a missing parent hides its required descendants.
+    #[serde(default)]
+    pub required: bool,
     /// is a separator for joining the result values. Works only when `many` is set to `true` and there is no descendant config.
     #[serde(default)]
     pub join_sep: String,
     /// is a flag that indicates whether the result is expecting to be an array or not.
     #[serde(default)]
     pub many: bool,
+    /// is the maximum number of matched elements to handle, if `many` is `true`. The rest of matched elements are ignored,
+    /// so it does not guarantee the number of items in the result, i.e. empty items are still skipped.
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// is the number of the first matched elements to ignore, if `many` is `true`. It is applied before `limit`,
+    /// and `index` of `enumerate` starts from `0` for the first handled element.
+    #[serde(default)]
+    pub skip: usize,
     ///  adds a index field to the result if it is an array of objects.
     #[serde(default)]
     pub enumerate: bool,
+    /// if it is `true` then the order of the result array is reversed, if `many` is `true`.
+    /// The array is reversed after `limit` and `skip` are applied and before `enumerate` and `join_sep`,
+    /// so `index` reflects the final order.
+    #[serde(default)]
+    pub reverse: bool,
+    /// if it is `true` then duplicate items are removed from the result array, keeping the first occurrence.
+    /// Works only when `many` is set to `true`, objects are compared by all of their fields.
+    /// It is applied after `keep_where` and before `reverse` and `enumerate`.
+    #[serde(default)]
+    pub unique: bool,
     /// parent's `base_path` (and parent's selector) will be used if it is set to `true`.
     #[serde(default)]
     pub inherit: bool,
@@ -50,19 +187,131 @@ pub struct Config {
     /// if it is `true` then finder will unpack descendant map into parent map.
     #[serde(default)]
     pub flatten: bool,
-    /// if it is `true` then finder will split `base_path` by `,` for more flexibility. Not implemented yet.
+    /// if it is `true` then the field is always present in the parent object, even if its value is empty:
+    /// a missing value is recorded as `null`, other empty values (i.e. an empty string or `0`) are kept as is.
+    /// An object that contains only such empty values is still considered empty.
+    #[serde(default)]
+    pub emit_null: bool,
+    /// if it is `true` then the extraction of this config (including its descendant configs) runs inside
+    /// `std::panic::catch_unwind`. A panic is treated as an empty value and recorded as a warning,
+    /// which is available through `Finder::parse_with_warnings`, so the other fields are still extracted.
+    ///
+    /// It is a defensive measure: it adds a small overhead to every extraction of the config,
+    /// the panic message is still printed by the panic hook, and it has no effect if the binary is built with `panic = "abort"`.
+    /// Changes made to the document before the panic (i.e. by `remove_selection`) are not rolled back.
+    #[serde(default)]
+    pub isolate_panics: bool,
+    /// if it is `true` then finder will put descendant values into an array (tuple) in the order of `children`,
+    /// instead of a map. Missing descendant values are represented by `null`, so the positions are preserved.
+    #[serde(default)]
+    pub as_tuple: bool,
+    /// is a map of keys to rename (`old: new`) in every result object. Works only when `many` is set to `true` and there are descendant configs.
+    /// Keys are renamed at once, so they can be swapped (`a: b, b: a`) or chained (`a: b, b: c`). Every key keeps its position.
+    /// If a renamed key collides with another key, the renamed value wins; if several keys are renamed to the same key, the last one wins.
+    #[serde(default)]
+    pub rename_keys: HashMap<String, String>,
+    /// is a pair of a descendant config name and an expected value. If it is set, then only result objects
+    /// where the field is equal to the expected value will be kept. Works only when `many` is set to `true` and there are descendant configs.
+    /// The expected value may start with an operator: `!=`, `>` or `<`. `>` and `<` compare numbers if both sides are numbers.
+    /// A missing field satisfies only the `!=` operator. It is applied before `rename_keys` and `enumerate`.
+    #[serde(default)]
+    pub keep_where: Option<(String, String)>,
+    /// if it is `true` then finder stops collecting result objects at the first matched element that gives an empty object,
+    /// instead of skipping it. Works only when `many` is set to `true` and there are descendant configs.
+    #[serde(default)]
+    pub stop_on_empty: bool,
+    /// is a key to insert the total number of result objects into every result object, i.e. for "1 of N" displays.
+    /// The total is counted after `keep_where` and `stop_on_empty` are applied, so it matches the length of the result array.
+    /// Works only when `many` is set to `true` and there are descendant configs.
+    #[serde(default)]
+    pub with_total: Option<String>,
+    /// if it is `true` then finder inserts the metadata of the matched element into every result object:
+    /// an object with the `tag` name and the `id` and `class` attributes (if they are present).
+    /// Works only when `many` is set to `true` and there are descendant configs.
+    #[serde(default)]
+    pub include_meta: bool,
+    /// is a key for the metadata inserted by `include_meta`. Default is `_meta`.
+    #[serde(default)]
+    pub meta_key: Option<String>,
+    /// is a css selector of headings, i.e. `h2,h3`. If it is set, then finder inserts the text of the nearest heading
+    /// that precedes the matched element in the document order (including its ancestors) into every result object
+    /// under the `_section` key. If there is no such heading, the key is not inserted.
+    /// Works only when `many` is set to `true` and there are descendant configs.
+    #[serde(default)]
+    pub section_heading: Option<String>,
+    /// is a list of descendant config names, which values are combined into a stable id of the object,
+    /// i.e. `[url, title]`. The id is a short base62 hash of the values (missing values are taken as `null`),
+    /// it is computed before `rename_keys` and it is inserted under the `id_key` key.
+    /// Requires descendant configs and can not be used with `as_tuple` option.
+    #[serde(default)]
+    pub id_from: Vec<String>,
+    /// is a key for the id inserted by `id_from`. Default is `_id`.
+    #[serde(default)]
+    pub id_key: Option<String>,
+    /// if it is greater than `0` then finder splits the matched elements (in document order) into groups of the given size
+    /// and produces an object per group, where the N-th descendant config handles the N-th element of the group,
+    /// i.e. it allows to pair flat `<dt>` and `<dd>` siblings. Descendant configs usually have `inherit` set to `true`.
+    /// It requires `many` to be `true` and exactly `group_by` descendant configs, and it can not be used together with `as_tuple`.
+    #[serde(default)]
+    pub group_by: usize,
+    /// if it is `true` then finder expects `base_path` to match a `<table>` element
+    /// and produces an array of objects (one per body row) keyed by the header cells text.
+    /// The header is the first row consisting only of `<th>` cells. Rows before the header are skipped.
+    /// A cell with `colspan` occupies several columns and its value goes under the key of the first one,
+    /// a header cell with `colspan` gives its key to all the columns it spans. `rowspan` is not supported.
+    /// Cell values are texts with normalized spaces, `pipeline` and `cast` are applied to every cell.
+    /// If `many` is `true` then the result is an array of such arrays, one per matched table.
+    /// It can not be used together with `extract` or `children` options.
+    #[serde(default)]
+    pub table: bool,
+    /// if it is `true` then finder will split `base_path` by top-level `,` (commas inside brackets, parentheses
+    /// or quotes are kept) and match every part separately. The result contains the nodes matched by the first part,
+    /// then the nodes matched by the second part (that were not matched before), and so on.
     #[serde(default)]
     pub split_path: bool,
     /// is a list of predefined procedures to apply to the result.
     /// Each procedure (pipeline element) is represented by a list of strings.
     /// Currently supported procedures are:
-    /// `regex`, `replace`, `extract_json`, `trim_space`,
-    /// `trim`, `html_unescape`, `policy_highlight`, `policy_table`, `policy_list`, `policy_common`.
+    /// `regex`, `regex_find`, `regex_group`, `regex_all`, `replace`, `regex_replace`, `prepend`, `append`, `default`, `coalesce`, `extract_json`, `trim_space`,
+    /// `trim`, `normalize_spaces`, `normalize_text`, `html_unescape`, `html_escape`, `policy_highlight`, `policy_table`, `policy_list`, `policy_common`,
+    /// `slugify`, `readable_text`, `first_sentence`, `first_words`, `slice`, `between`, `when_match`, `ratio`, `short_hash`, `check_shape`, `csv_cell`, `titlecase`, `clean_tokens`, `bucket`, `url_decode`, `money`, `printf`, `ordinal`, `parse_bytes`, `sum_numbers`,
+    /// `keep_categories` (requires `unicode_categories` feature), `to_utc`, `humanize_time` (requires `timezones` feature),
+    /// `canonical_url`, `resolve_url` (requires `urls` feature), `detect_lang` (requires `lang_detect` feature),
+    /// `hash` (requires `hashing` feature), `markdown`, `to_markdown` (requires `markdown` feature).
+    /// Terminal procedures produce a structured value (not casted) and must be the last in the pipeline:
+    /// `numbers_with_units`, `srcset`, `pick_largest_image`, `decl_params`, `text_metrics`, `split`, `contains_ci`.
+    /// Also it may reference custom procedures registered with `Pipeline::register`.
     #[serde(default)]
     pub pipeline: Vec<Vec<String>>,
+    /// is a list of procedures (like `pipeline`) to apply to every object assembled from the descendant configs.
+    /// The object is serialized into a compact JSON string, which is passed through the procedures.
+    /// The string result is kept as is, unless `post_pipeline_json` is `true`.
+    /// A structured result of a terminal procedure is kept as is.
+    /// Works only when there are descendant configs and `as_tuple` is not set.
+    #[serde(default)]
+    pub post_pipeline: Vec<Vec<String>>,
+    /// if it is `true` then the string result of `post_pipeline` is parsed back as JSON.
+    /// If the result is not a valid JSON, the value is `null`.
+    #[serde(default)]
+    pub post_pipeline_json: bool,
+    /// is a list of procedures (like `pipeline`) to apply to the whole result, i.e. a global `replace`.
+    /// The result of `Finder::parse` (the object with the root config name as a key) is serialized into a compact JSON string,
+    /// which is passed through the procedures and then parsed back as JSON, so the procedures must keep it a valid JSON.
+    /// If the string result is not a valid JSON, the whole result is `null`. A structured result of a terminal procedure is kept as is.
+    /// It is applied by all methods that return the result under the root config name (`parse`, `parse_document`,
+    /// `parse_with_deadline`, `try_parse`, etc.). It can be set only in the root config.
+    #[serde(default)]
+    pub final_pipeline: Vec<Vec<String>>,
     /// is a list of descendant `Config`.
     #[serde(default)]
     pub children: Vec<Config>,
+    /// is a list of pairs of a css selector and descendant configs, which are used instead of `children`
+    /// for the elements that match the selector, i.e. `[data-kind="video"]`. It allows to produce differently shaped objects
+    /// from heterogeneous items. The first matching pair wins, `children` are used if no pair matches.
+    /// Configs of the pairs are not reachable by `Finder::parse_each`, but `Finder::parse_field` resolves them for every matched element.
+    /// It can not be used with `as_tuple` and `group_by` options.
+    #[serde(default)]
+    pub variants: Vec<(String, Vec<Config>)>,
 }
 
 impl Config {
@@ -91,15 +340,45 @@ impl Config {
         if self.name.is_empty() {
             return Err(ValidationError::FieldIsMissing("name".to_string()));
         }
+        if self.page.is_some() {
+            if !self.base_path.is_empty()
+                || !self.extract.is_empty()
+                || !self.children.is_empty()
+                || !self.variants.is_empty()
+            {
+                return Err(ValidationError::PageWithSelection);
+            }
+            return Ok(());
+        }
         if self.base_path.is_empty() && !self.inherit {
             // The case when base_path is empty and inherit is true, resolved in Finder::new
             return Err(ValidationError::FieldIsMissing("base_path".to_string()));
         }
         let must_extract = !self.extract.is_empty();
-        let must_dive = !self.children.is_empty();
+        let must_dive = !self.children.is_empty() || !self.variants.is_empty();
+        if self.table {
+            if must_extract || must_dive {
+                return Err(ValidationError::TableWithExtractOrDive);
+            }
+            return Ok(());
+        }
         if must_extract == must_dive {
             return Err(ValidationError::ExtractOrDive);
         }
+        if !self.id_from.is_empty() && (!must_dive || self.as_tuple) {
+            return Err(ValidationError::InvalidIdFrom);
+        }
+        if self.group_by > 0
+            && (!self.many || self.as_tuple || self.children.len() != self.group_by)
+        {
+            return Err(ValidationError::InvalidGroupBy);
+        }
+        if !self.variants.is_empty() && (self.as_tuple || self.group_by > 0) {
+            return Err(ValidationError::InvalidVariants);
+        }
+        if self.extract == "count" && !self.many {
+            return Err(ValidationError::CountRequiresMany);
+        }
         Ok(())
     }
 }
@@ -144,6 +423,69 @@ mod tests {
         assert!(cfg.validate().is_ok());
     }
 
+    #[test]
+    fn config_page_is_valid() {
+        let cfg_yml: &str = r"
+        name: title
+        page: title
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn config_page_with_base_path() {
+        let cfg_yml: &str = r"
+        name: title
+        base_path: head title
+        page: title
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn config_group_by_children_mismatch() {
+        let cfg_yml: &str = r"
+        name: pairs
+        base_path: dl > *
+        many: true
+        group_by: 2
+        children:
+          - name: term
+            inherit: true
+            extract: text
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+        assert!(matches!(
+            cfg.validate(),
+            Err(ValidationError::InvalidGroupBy)
+        ));
+    }
+
+    #[test]
+    fn config_table_is_valid() {
+        let cfg_yml: &str = r"
+        name: nutrition
+        base_path: table
+        table: true
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn config_table_with_extract() {
+        let cfg_yml: &str = r"
+        name: nutrition
+        base_path: table
+        table: true
+        extract: text
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+        assert!(cfg.validate().is_err());
+    }
+
     #[test]
     #[cfg(feature = "json_cfg")]
     fn config_json_is_valid() {
@@ -7,6 +7,29 @@ pub enum ValidationError {
     FieldIsMissing(String),
     #[error("it is only possible to use either 'extract' or 'children' options")]
     ExtractOrDive,
+    #[error("'table' option can not be used with 'extract' or 'children' options")]
+    TableWithExtractOrDive,
+    #[error("'page' option can not be used with 'base_path', 'extract' or 'children' options")]
+    PageWithSelection,
+    #[error("'group_by' option requires 'many' option and exactly `group_by` children, and can not be used with 'as_tuple' option")]
+    InvalidGroupBy,
+    #[error(
+        "'id_from' option requires 'children' option and can not be used with 'as_tuple' option"
+    )]
+    InvalidIdFrom,
+    #[error("'variants' option can not be used with 'as_tuple' or 'group_by' options")]
+    InvalidVariants,
+    #[error("'count' extract type requires 'many' option")]
+    CountRequiresMany,
+    #[error("'final_pipeline' option can be set only in the root config")]
+    FinalPipelineNotRoot,
+}
+
+/// Errors that can occur during the extraction with `Finder::try_parse`.
+#[derive(Error, Debug)]
+pub enum ExtractError {
+    #[error("required fields are missing: {}", .0.join(", "))]
+    MissingRequired(Vec<String>),
 }
 
 /// Errors that can occur during the pipeline initialization.
@@ -18,9 +41,16 @@ pub enum PipelineError {
     ProcDoesNotExist(String),
     #[error("pipeline proc `{0}`: not enough arguments, require {1}, got {2}")]
     ProcNotEnoughArguments(String, usize, usize),
+    #[error(
+        "pipeline proc `{0}` produces a structured value and must be the last in the pipeline"
+    )]
+    ProcMustBeLast(String),
+    #[error("pipeline proc `{0}`: invalid argument `{1}`")]
+    ProcInvalidArgument(String, String),
 }
 
-/// Errors that can be encountered only during creation of the `Finder` instance.
+/// Errors that can be encountered during creation of the `Finder` instance,
+/// or during parsing with the limits (i.e. `Finder::parse_with_deadline`).
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error("matcher can be empty only if `inherit` option is set to true")]
@@ -29,4 +59,18 @@ pub enum ParseError {
     Validation(#[from] ValidationError),
     #[error(transparent)]
     Pipeline(#[from] PipelineError),
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+    #[error("`{0}`: `cast` option is incompatible with {1}")]
+    IncompatibleCast(String, String),
+    #[error("`{0}`: `join_sep` option is incompatible with {1}")]
+    IncompatibleJoin(String, String),
+    #[error("`{0}`: `pipeline` option is incompatible with {1}")]
+    IncompatiblePipeline(String, String),
+    #[error("invalid css selector `{0}`")]
+    InvalidSelector(String),
+    #[error("parsing deadline exceeded")]
+    Timeout,
+    #[error("input html is too large: {0} bytes, the limit is {1} bytes")]
+    InputTooLarge(usize, usize),
 }
@@ -1,40 +1,219 @@
 use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
 
-use dom_query::{Document, Matcher, Selection};
+use dom_query::{Document, Matcher, NodeData, NodeId, NodeRef, Selection};
+use hashbrown::{HashMap, HashSet};
+use regex::Regex;
 use tendril::StrTendril;
 
-use crate::errors::ParseError;
+use crate::errors::{ExtractError, ParseError, ValidationError};
 
-use super::config::{CastType, Config};
-use super::pipeline::Pipeline;
+use super::config::{CastType, Config, InputMode};
+use super::pipeline::{short_hash, Pipeline, PipelineOutput, SHORT_HASH_DEFAULT_LEN};
 use super::value::{InnerMap, Value};
+use super::value_json::{value_from_json, SerializeOptions};
 
 /// The name of the field that contains the index of the element in the result array.
 const INDEX_FIELD: &str = "index";
+/// The default name of the field that contains the metadata of the matched element.
+const META_FIELD: &str = "_meta";
+const ID_FIELD: &str = "_id";
+const SECTION_FIELD: &str = "_section";
 
 const EXTRACT_TEXT: &str = "text";
 const EXTRACT_INNER_TEXT: &str = "inner_text";
 const EXTRACT_IMMEDIATE_TEXT: &str = "immediate_text";
 const EXTRACT_HTML: &str = "html";
 const EXTRACT_INNER_HTML: &str = "inner_html";
+const EXTRACT_COMMENT: &str = "comment";
+const EXTRACT_ACCESSIBLE_NAME: &str = "accessible_name";
+const EXTRACT_SIBLING_INDEX: &str = "sibling_index";
+const EXTRACT_TAG_NAME: &str = "tag_name";
+const EXTRACT_ATTRS: &str = "attrs";
+const EXTRACT_DATA: &str = "data";
+const EXTRACT_CLASS_LIST: &str = "class_list";
+const EXTRACT_COUNT: &str = "count";
+const DATA_ATTR_PREFIX: &str = "data-";
+/// The suffix that turns an attribute extract into the list of its whitespace-separated tokens, i.e. `class[]`.
+const EXTRACT_LIST_SUFFIX: &str = "[]";
+/// The prefix that forces `extract` to be interpreted as an attribute name, even if it is a reserved word.
+const EXTRACT_ATTR_PREFIX: &str = "attr:";
+
+/// Holds the state of a single parsing run.
+#[derive(Default)]
+struct ParseContext {
+    deadline: Option<Instant>,
+    timed_out: Cell<bool>,
+    base_url: Option<String>,
+    warnings: RefCell<Vec<String>>,
+    /// the paths of the required configs, which selections are missing.
+    missing: RefCell<Vec<String>>,
+    /// the number of matched nodes by the config path, it is collected only if it is set.
+    counts: Option<RefCell<HashMap<String, usize>>>,
+}
+
+impl ParseContext {
+    /// Creates a context for the document with the given root, it holds the document's `<base href>`.
+    fn from_root(root: &Selection) -> ParseContext {
+        ParseContext {
+            base_url: root
+                .select_single("base[href]")
+                .attr("href")
+                .map(|href| href.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if the deadline is exceeded. Once exceeded, it always returns `true`.
+    fn is_expired(&self) -> bool {
+        if self.timed_out.get() {
+            return true;
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.timed_out.set(true);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Gt,
+    Lt,
+}
+
+/// A set of descendant configs, that is used for the elements matching the selector, see `Config::variants`.
+#[derive(Debug)]
+struct Variant<'a> {
+    selector: Cow<'a, str>,
+    matcher: Matcher,
+    children: Vec<Finder<'a>>,
+}
+
+/// A predicate that is applied to the result objects by the `keep_where` option.
+#[derive(Debug)]
+struct KeepWhere<'a> {
+    field: Cow<'a, str>,
+    op: CmpOp,
+    expected: Cow<'a, str>,
+}
+
+impl<'a> KeepWhere<'a> {
+    fn new(field: &str, expected: &str) -> KeepWhere<'a> {
+        let (op, expected) = if let Some(rest) = expected.strip_prefix("!=") {
+            (CmpOp::Ne, rest)
+        } else if let Some(rest) = expected.strip_prefix('>') {
+            (CmpOp::Gt, rest)
+        } else if let Some(rest) = expected.strip_prefix('<') {
+            (CmpOp::Lt, rest)
+        } else {
+            (CmpOp::Eq, expected)
+        };
+        let expected = if op == CmpOp::Eq {
+            expected
+        } else {
+            expected.trim_start()
+        };
+        KeepWhere {
+            field: Cow::from(field.to_string()),
+            op,
+            expected: Cow::from(expected.to_string()),
+        }
+    }
+
+    /// Returns the `(field, expected value)` pair as it is accepted by the config.
+    fn to_raw(&self) -> (String, String) {
+        let op = match self.op {
+            CmpOp::Eq => "",
+            CmpOp::Ne => "!=",
+            CmpOp::Gt => ">",
+            CmpOp::Lt => "<",
+        };
+        (self.field.to_string(), format!("{}{}", op, self.expected))
+    }
+
+    fn is_match(&self, m: &InnerMap) -> bool {
+        let actual = match m.get(self.field.as_ref()) {
+            Some(Value::String(val)) => Cow::from(val.as_str()),
+            Some(Value::Int(val)) => Cow::from(val.to_string()),
+            Some(Value::Float(val)) => Cow::from(val.to_string()),
+            Some(Value::Bool(val)) => Cow::from(val.to_string()),
+            _ => return self.op == CmpOp::Ne,
+        };
+        let ord = match (actual.parse::<f64>(), self.expected.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(actual.as_ref().cmp(self.expected.as_ref())),
+        };
+        match self.op {
+            CmpOp::Eq => ord == Some(Ordering::Equal),
+            CmpOp::Ne => ord != Some(Ordering::Equal),
+            CmpOp::Gt => ord == Some(Ordering::Greater),
+            CmpOp::Lt => ord == Some(Ordering::Less),
+        }
+    }
+}
 
 /// Finder is the main struct that is used to parse the html
 #[derive(Debug)]
 pub struct Finder<'a> {
     name: Cow<'a, str>,
+    input_mode: InputMode,
+    base_path: Cow<'a, str>,
     extract: Cow<'a, str>,
     cast: CastType,
+    strict_cast: bool,
+    default_value: Option<Cow<'a, str>>,
+    /// the dotted path of the config, starting with the root config name.
+    path: Cow<'a, str>,
+    required: bool,
     join_sep: Cow<'a, str>,
     many: bool,
+    limit: Option<usize>,
+    skip: usize,
     enumerate: bool,
+    reverse: bool,
+    unique: bool,
     inherit: bool,
     parent: bool,
     first_occurrence: bool,
     remove_selection: bool,
     flatten: bool,
+    emit_null: bool,
+    isolate_panics: bool,
+    as_tuple: bool,
+    skip_hidden: bool,
+    role: Option<Cow<'a, str>>,
+    table: bool,
+    rename_keys: HashMap<String, String>,
+    keep_where: Option<KeepWhere<'a>>,
+    stop_on_empty: bool,
+    with_total: Option<Cow<'a, str>>,
+    meta_key: Option<Cow<'a, str>>,
+    section_heading: Option<(Cow<'a, str>, Matcher)>,
+    id_from: Vec<String>,
+    id_key: Option<Cow<'a, str>>,
+    decode_entities: bool,
+    trim_html: bool,
+    group_by: usize,
     children: Vec<Finder<'a>>,
+    variants: Vec<Variant<'a>>,
     matcher: Option<Matcher>,
+    split_matchers: Vec<Matcher>,
+    ci_class: bool,
+    ci_matcher: Option<Matcher>,
+    filter: Option<(Cow<'a, str>, Regex)>,
     pipeline: Option<Pipeline<'a>>,
+    post_pipeline: Option<Pipeline<'a>>,
+    post_pipeline_json: bool,
+    final_pipeline: Option<Pipeline<'a>>,
 }
 
 impl<'a> Finder<'a> {
@@ -58,49 +237,173 @@ impl<'a> Finder<'a> {
     /// assert!(finder.is_ok());
     /// ```
     pub fn new<'b>(config: &'b Config) -> Result<Finder<'a>, ParseError> {
-        Finder::from_config(config, true)
+        Finder::from_config(config, None)
     }
 
-    fn from_config<'b>(config: &'b Config, is_root: bool) -> Result<Finder<'a>, ParseError> {
+    /// Creates the finder from the config, the `parent_path` is the dotted path of the parent config,
+    /// it is `None` for the root config.
+    fn from_config<'b>(
+        config: &'b Config,
+        parent_path: Option<&str>,
+    ) -> Result<Finder<'a>, ParseError> {
+        let is_root = parent_path.is_none();
+        let path = match parent_path {
+            Some(parent_path) => format!("{}.{}", parent_path, config.name),
+            None => config.name.clone(),
+        };
         config.validate()?;
-        let base_path = config.base_path.as_str();
+        let (base_path, extract) = match config.page {
+            Some(page) => (page.base_path(), page.extract()),
+            None => (config.base_path.as_str(), config.extract.as_str()),
+        };
         let matcher = if !base_path.is_empty() {
             Matcher::new(base_path).ok()
         } else {
             None
         };
 
+        // the parts are checked first, so an invalid part is reported instead of the whole path
+        let split_matchers = if config.split_path && !base_path.is_empty() {
+            split_selector(base_path)
+                .into_iter()
+                .map(|part| {
+                    Matcher::new(part).map_err(|_| ParseError::InvalidSelector(part.to_string()))
+                })
+                .collect::<Result<Vec<Matcher>, _>>()?
+        } else {
+            Vec::new()
+        };
+
         if matcher.is_none() && (is_root || !config.inherit) {
             return Err(ParseError::RequireMatcher);
         }
+        let ci_matcher = if config.ci_class {
+            relax_class_selectors(base_path)
+                .filter(|path| path != base_path)
+                .and_then(|path| Matcher::new(&path).ok())
+        } else {
+            None
+        };
+
+        let section_heading = match config.section_heading {
+            Some(ref selector) => {
+                let matcher = Matcher::new(selector)
+                    .map_err(|_| ParseError::InvalidSelector(selector.clone()))?;
+                Some((Cow::from(selector.clone()), matcher))
+            }
+            None => None,
+        };
+
+        let filter = match config.filter {
+            Some((ref attr, ref pattern)) => Some((Cow::from(attr.clone()), Regex::new(pattern)?)),
+            None => None,
+        };
 
         let pipeline = if !config.pipeline.is_empty() {
             Some(Pipeline::new(&config.pipeline)?)
         } else {
             None
         };
+        validate_output(config, pipeline.as_ref())?;
+        let post_pipeline = if !config.post_pipeline.is_empty() {
+            Some(Pipeline::new(&config.post_pipeline)?)
+        } else {
+            None
+        };
+        let final_pipeline = match (config.final_pipeline.is_empty(), is_root) {
+            (true, _) => None,
+            (false, true) => Some(Pipeline::new(&config.final_pipeline)?),
+            (false, false) => return Err(ValidationError::FinalPipelineNotRoot.into()),
+        };
+
         let mut p = Finder {
             name: Cow::from(config.name.clone()),
-            extract: Cow::from(config.extract.clone()),
+            input_mode: config.input_mode,
+            base_path: Cow::from(base_path.to_string()),
+            extract: Cow::from(extract.to_string()),
             cast: config.cast,
+            strict_cast: config.strict_cast,
+            default_value: config.default_value.clone().map(Cow::from),
+            path: Cow::from(path.clone()),
+            required: config.required,
             join_sep: Cow::from(config.join_sep.clone()),
             many: config.many,
+            limit: config.limit,
+            skip: config.skip,
             enumerate: config.enumerate,
+            reverse: config.reverse,
+            unique: config.unique,
             inherit: config.inherit,
             parent: config.parent,
             first_occurrence: config.first_occurrence,
             remove_selection: config.remove_selection,
             flatten: config.flatten,
+            emit_null: config.emit_null,
+            isolate_panics: config.isolate_panics,
+            as_tuple: config.as_tuple,
+            skip_hidden: config.skip_hidden,
+            role: config
+                .role
+                .as_ref()
+                .map(|role| Cow::from(role.to_ascii_lowercase())),
+            table: config.table,
+            rename_keys: config.rename_keys.clone(),
+            keep_where: config
+                .keep_where
+                .as_ref()
+                .map(|(field, expected)| KeepWhere::new(field, expected)),
+            stop_on_empty: config.stop_on_empty,
+            with_total: config.with_total.clone().map(Cow::from),
+            meta_key: config
+                .include_meta
+                .then(|| Cow::from(config.meta_key.as_deref().unwrap_or(META_FIELD).to_string())),
+            section_heading,
+            id_from: config.id_from.clone(),
+            id_key: (!config.id_from.is_empty())
+                .then(|| Cow::from(config.id_key.as_deref().unwrap_or(ID_FIELD).to_string())),
+            decode_entities: config.decode_entities,
+            trim_html: config.trim_html,
+            group_by: config.group_by,
             children: Vec::new(),
+            variants: Vec::new(),
             matcher,
+            split_matchers,
+            ci_class: config.ci_class,
+            ci_matcher,
+            filter,
             pipeline,
+            post_pipeline,
+            post_pipeline_json: config.post_pipeline_json,
+            final_pipeline,
         };
 
         for inline_config in config.children.iter() {
-            p.children.push(Finder::from_config(inline_config, false)?);
+            p.children
+                .push(Finder::from_config(inline_config, Some(&path))?);
+        }
+        for (selector, configs) in config.variants.iter() {
+            let matcher = Matcher::new(selector)
+                .map_err(|_| ParseError::InvalidSelector(selector.clone()))?;
+            let children = configs
+                .iter()
+                .map(|c| Finder::from_config(c, Some(&path)))
+                .collect::<Result<Vec<_>, _>>()?;
+            p.variants.push(Variant {
+                selector: Cow::from(selector.clone()),
+                matcher,
+                children,
+            });
         }
         Ok(p)
     }
+    /// Builds the document from the html according to the `input_mode` option.
+    fn build_document(&self, html: &str) -> Document {
+        match self.input_mode {
+            InputMode::Document => Document::from(html),
+            InputMode::Fragment => Document::fragment(html),
+        }
+    }
+
     /// Either returns the matcher of or panics
     fn get_matcher(&self) -> &Matcher {
         match self.matcher {
@@ -134,7 +437,7 @@ impl<'a> Finder<'a> {
     /// assert_eq!(link.unwrap(), "https://example.com");
     /// ```
     pub fn parse(&self, html: &str) -> Value {
-        let doc = Document::from(html);
+        let doc = self.build_document(html);
         self.parse_document(&doc)
     }
 
@@ -146,10 +449,347 @@ impl<'a> Finder<'a> {
     /// `Value::Object`
     pub fn parse_document(&self, doc: &Document) -> Value {
         let sel = Selection::from(doc.root());
-        let val = self.parse_value(&sel);
-        let mut m: InnerMap = InnerMap::default();
-        m.insert(self.name.to_string(), val);
-        Value::Object(m)
+        let val = self.parse_value_with(&sel, &ParseContext::from_root(&sel));
+        self.wrap_result(val)
+    }
+
+    /// Parses the given html and returns the result as a `Value` without wrapping it into an object under the finder's `name`.
+    /// # Arguments
+    /// * `html` - the html to parse
+    ///
+    /// # Returns
+    /// `Value` representing the parsed result
+    ///
+    /// # Examples
+    /// ```
+    /// use dom_finder::{Config, Finder};
+    /// let cfg_yml: &str = r"
+    /// name: all_links
+    /// base_path: html body a[href]
+    /// many: true
+    /// extract: href
+    /// ";
+    /// let cfg = Config::from_yaml(cfg_yml).unwrap();
+    /// let finder = Finder::new(&cfg).unwrap();
+    /// let html = r#"<html><body><a href="https://example.com">example</a></body></html>"#;
+    /// let res = finder.parse_unwrapped(html);
+    /// let link: Option<String> = res.from_path("0").and_then(|v| v.into());
+    /// assert_eq!(link.unwrap(), "https://example.com");
+    /// ```
+    pub fn parse_unwrapped(&self, html: &str) -> Value {
+        let doc = self.build_document(html);
+        let sel = Selection::from(doc.root());
+        self.parse_value_with(&sel, &ParseContext::from_root(&sel))
+    }
+
+    /// Parses the given html, but handles only the descendant config with the given name,
+    /// skipping all other descendant configs.
+    /// # Arguments
+    /// * `html` - the html to parse
+    /// * `field_name` - the name of the descendant config. Nested configs can be reached with a dotted path,
+    ///   i.e. `results.url`. The path doesn't include the name of the root config.
+    ///
+    /// # Returns
+    /// `Value` of the field. If any config on the path has `many` option, the result is an array of field values.
+    /// If there is no such field, `Value::Null` is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use dom_finder::{Config, Finder};
+    /// let cfg_yml: &str = r"
+    /// name: root
+    /// base_path: html
+    /// children:
+    ///   - name: title
+    ///     base_path: h1
+    ///     extract: text
+    ///   - name: links
+    ///     base_path: a[href]
+    ///     many: true
+    ///     extract: href
+    /// ";
+    /// let cfg = Config::from_yaml(cfg_yml).unwrap();
+    /// let finder = Finder::new(&cfg).unwrap();
+    /// let html = r#"<html><body><h1>Title</h1><a href="https://example.com">example</a></body></html>"#;
+    /// let title: Option<String> = finder.parse_field(html, "title").into();
+    /// assert_eq!(title.unwrap(), "Title");
+    /// ```
+    pub fn parse_field(&self, html: &str, field_name: &str) -> Value {
+        let doc = self.build_document(html);
+        let sel = Selection::from(doc.root());
+        self.parse_field_value(&sel, field_name, &ParseContext::from_root(&sel))
+    }
+
+    /// Parses the given html and invokes the callback for every item of the top-level descendant config with the given name,
+    /// instead of collecting them into an array. It reduces peak memory for big `many` results.
+    ///
+    /// Items are produced like `Finder::parse` does, except the options that require the whole array:
+    /// `with_total` and `default_value` are ignored, the `index` of `enumerate` counts items passed to the callback.
+    /// If the descendant config has `reverse`, all its items are collected before the first callback invocation.
+    /// If the descendant config is not `many`, it has `join_sep` or `extract: count`, the callback is invoked once with the whole value.
+    /// Empty items are skipped. If there is no descendant config with the given name, the callback is never invoked.
+    /// # Arguments
+    /// * `html` - the html to parse
+    /// * `field_name` - the name of the top-level descendant config
+    /// * `f` - the callback that receives every item
+    ///
+    /// # Examples
+    /// ```
+    /// use dom_finder::{Config, Finder};
+    /// let cfg_yml: &str = r"
+    /// name: root
+    /// base_path: html
+    /// children:
+    ///   - name: links
+    ///     base_path: a[href]
+    ///     many: true
+    ///     extract: href
+    /// ";
+    /// let cfg = Config::from_yaml(cfg_yml).unwrap();
+    /// let finder = Finder::new(&cfg).unwrap();
+    /// let html = r#"<html><body><a href="/1">1</a><a href="/2">2</a></body></html>"#;
+    /// let mut links = vec![];
+    /// finder.parse_each(html, "links", |v| links.push(v));
+    /// assert_eq!(links.len(), 2);
+    /// ```
+    pub fn parse_each<F: FnMut(Value)>(&self, html: &str, field_name: &str, mut f: F) {
+        let Some(child) = self.children.iter().find(|child| child.name == field_name) else {
+            return;
+        };
+        let doc = self.build_document(html);
+        let root = Selection::from(doc.root());
+        let sel = self.select(&root);
+        if !sel.exists() {
+            return;
+        }
+        let ctx = ParseContext::from_root(&root);
+        if self.many {
+            for item in sel.iter() {
+                child.parse_items_with(&item, &ctx, &mut f);
+            }
+        } else {
+            child.parse_items_with(&sel, &ctx, &mut f);
+        }
+    }
+
+    /// Parses the items of the `many` selection one by one and passes them to the callback.
+    fn parse_items_with(&self, root: &Selection, ctx: &ParseContext, f: &mut dyn FnMut(Value)) {
+        if self.is_count() {
+            // a count is meaningful even if it is zero
+            f(self.parse_value_with(root, ctx));
+            return;
+        }
+        if !self.many || (!self.has_children() && !self.join_sep.is_empty()) {
+            let v = self.parse_value_with(root, ctx);
+            if !v.is_empty() {
+                f(v);
+            }
+            return;
+        }
+        let sel = self.select(root);
+        if !sel.exists() {
+            return;
+        }
+        let mut emit = |v: Value| {
+            if !v.is_empty() {
+                f(v);
+            }
+        };
+        if self.table || self.as_tuple {
+            let items = sel.iter().take_while(|_| !ctx.is_expired()).map(|item| {
+                if self.table {
+                    self.parse_table(&item, ctx)
+                } else {
+                    self.parse_children_to_tuple(&item, ctx)
+                }
+            });
+            if self.reverse {
+                items
+                    .collect::<Vec<Value>>()
+                    .into_iter()
+                    .rev()
+                    .for_each(emit);
+            } else {
+                items.for_each(emit);
+            }
+        } else if self.has_children() {
+            let mut index = 0;
+            self.produce_maps(&sel, ctx, &mut |_, mut m| {
+                self.finish_map(&mut m, index);
+                index += 1;
+                emit(self.handle_object(m));
+            });
+        } else {
+            self.produce_outputs(&sel, ctx, &mut |out| {
+                emit(cast_output(out, self.cast, self.strict_cast))
+            });
+        }
+        if self.remove_selection {
+            sel.remove();
+        }
+    }
+
+    /// Returns the effective config of the finder, i.e. the config tree after all shortcuts are expanded
+    /// (`page` is replaced with the corresponding `base_path` and `extract`).
+    /// Empty pipeline elements are omitted.
+    ///
+    /// # Examples
+    /// ```
+    /// use dom_finder::{Config, Finder};
+    /// let cfg_yml: &str = r"
+    /// name: root
+    /// base_path: html
+    /// children:
+    ///   - name: title
+    ///     page: title
+    /// ";
+    /// let cfg = Config::from_yaml(cfg_yml).unwrap();
+    /// let finder = Finder::new(&cfg).unwrap();
+    /// let effective = finder.effective_config();
+    /// assert_eq!(effective.children[0].base_path, "head title");
+    /// assert_eq!(effective.children[0].extract, "text");
+    /// ```
+    pub fn effective_config(&self) -> Config {
+        Config {
+            name: self.name.to_string(),
+            input_mode: self.input_mode,
+            base_path: self.base_path.to_string(),
+            filter: self
+                .filter
+                .as_ref()
+                .map(|(attr, re)| (attr.to_string(), re.as_str().to_string())),
+            skip_hidden: self.skip_hidden,
+            ci_class: self.ci_class,
+            role: self.role.as_ref().map(|r| r.to_string()),
+            page: None,
+            extract: self.extract.to_string(),
+            cast: self.cast,
+            strict_cast: self.strict_cast,
+            default_value: self.default_value.as_ref().map(|v| v.to_string()),
+            required: self.required,
+            join_sep: self.join_sep.to_string(),
+            many: self.many,
+            limit: self.limit,
+            skip: self.skip,
+            enumerate: self.enumerate,
+            reverse: self.reverse,
+            unique: self.unique,
+            inherit: self.inherit,
+            parent: self.parent,
+            first_occurrence: self.first_occurrence,
+            remove_selection: self.remove_selection,
+            flatten: self.flatten,
+            emit_null: self.emit_null,
+            isolate_panics: self.isolate_panics,
+            as_tuple: self.as_tuple,
+            rename_keys: self.rename_keys.clone(),
+            keep_where: self.keep_where.as_ref().map(|k| k.to_raw()),
+            stop_on_empty: self.stop_on_empty,
+            with_total: self.with_total.as_ref().map(|k| k.to_string()),
+            include_meta: self.meta_key.is_some(),
+            meta_key: self.meta_key.as_ref().map(|k| k.to_string()),
+            section_heading: self
+                .section_heading
+                .as_ref()
+                .map(|(selector, _)| selector.to_string()),
+            id_from: self.id_from.clone(),
+            id_key: self.id_key.as_ref().map(|k| k.to_string()),
+            decode_entities: self.decode_entities,
+            trim_html: self.trim_html,
+            group_by: self.group_by,
+            table: self.table,
+            split_path: !self.split_matchers.is_empty(),
+            pipeline: self
+                .pipeline
+                .as_ref()
+                .map(|p| p.raw().to_vec())
+                .unwrap_or_default(),
+            post_pipeline: self
+                .post_pipeline
+                .as_ref()
+                .map(|p| p.raw().to_vec())
+                .unwrap_or_default(),
+            post_pipeline_json: self.post_pipeline_json,
+            final_pipeline: self
+                .final_pipeline
+                .as_ref()
+                .map(|p| p.raw().to_vec())
+                .unwrap_or_default(),
+            children: self.children.iter().map(|c| c.effective_config()).collect(),
+            variants: self
+                .variants
+                .iter()
+                .map(|v| {
+                    let configs = v.children.iter().map(|c| c.effective_config()).collect();
+                    (v.selector.to_string(), configs)
+                })
+                .collect(),
+        }
+    }
+
+    fn parse_field_value(&self, root: &Selection, path: &str, ctx: &ParseContext) -> Value {
+        let (name, rest) = match path.split_once('.') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (path, None),
+        };
+        let variant_children = self.variants.iter().flat_map(|v| v.children.iter());
+        if !self
+            .children
+            .iter()
+            .chain(variant_children)
+            .any(|child| child.name == name)
+        {
+            return Value::Null;
+        }
+
+        let sel = self.select(root);
+        if !sel.exists() {
+            return Value::Null;
+        }
+        if !self.many {
+            return self.parse_group_field(&[sel], name, rest, None, ctx);
+        }
+        // result objects are produced like `Finder::parse` does, so the same objects are skipped
+        let mut values = Vec::new();
+        self.produce_maps(&sel, ctx, &mut |group, m| {
+            let v = self.parse_group_field(group, name, rest, Some(m), ctx);
+            if !v.is_empty() {
+                values.push(v);
+            }
+        });
+        Value::Array(values)
+    }
+
+    /// Parses the field with the given name (and the rest of the dotted path) from the group of elements.
+    /// The value of the field is taken from the already parsed map of the group, if it is given.
+    fn parse_group_field(
+        &self,
+        group: &[Selection],
+        name: &str,
+        rest: Option<&str>,
+        m: Option<InnerMap>,
+        ctx: &ParseContext,
+    ) -> Value {
+        let children = match group.first() {
+            Some(first) => self.children_for(first),
+            None => &self.children,
+        };
+        let Some((i, child)) = children
+            .iter()
+            .enumerate()
+            .find(|(_, child)| child.name == name)
+        else {
+            return Value::Null;
+        };
+        let pos = if self.group_by > 0 { i } else { 0 };
+        let Some(item) = group.get(pos) else {
+            return Value::Null;
+        };
+        match (rest, m) {
+            (None, Some(mut m)) if !child.flatten => m.shift_remove(name).unwrap_or(Value::Null),
+            (None, _) => child.parse_value_with(item, ctx),
+            (Some(rest), _) => child.parse_field_value(item, rest, ctx),
+        }
     }
 
     /// Parses the given Selection and returns the result as a `Value`
@@ -159,78 +799,666 @@ impl<'a> Finder<'a> {
     /// `Value` representing the parsed result
     ///
     /// This method handles different scenarios.
+    /// The `<base href>` element, that is used by the url pipeline procedures, is looked up only inside the `root`.
     pub fn parse_value(&self, root: &Selection) -> Value {
-        let sel: Selection = if self.inherit {
-            root.clone()
+        self.parse_value_with(root, &ParseContext::from_root(root))
+    }
+
+    /// Parses the given html like `Finder::parse`, but stops parsing if the `deadline` is exceeded.
+    ///
+    /// Cancellation is cooperative and coarse-grained: the deadline is checked after building the document,
+    /// between descendant configs and between the items of `many` selections.
+    /// So a single expensive step (i.e. parsing a huge html or applying a pipeline) is never interrupted.
+    /// # Arguments
+    /// * `html` - the html to parse
+    /// * `deadline` - the point in time when parsing must be stopped
+    ///
+    /// # Returns
+    /// `Result<Value, ParseError>`, where the error is `ParseError::Timeout` if the deadline was exceeded.
+    pub fn parse_with_deadline(&self, html: &str, deadline: Instant) -> Result<Value, ParseError> {
+        let doc = self.build_document(html);
+        let sel = Selection::from(doc.root());
+        let ctx = ParseContext {
+            deadline: Some(deadline),
+            ..ParseContext::from_root(&sel)
+        };
+        if ctx.is_expired() {
+            return Err(ParseError::Timeout);
+        }
+        let val = self.parse_value_with(&sel, &ctx);
+        if ctx.is_expired() {
+            return Err(ParseError::Timeout);
+        }
+        Ok(self.wrap_result(val))
+    }
+
+    /// Parses the given html like `Finder::parse`, but fails if the selection of any `required` config is missing.
+    /// # Arguments
+    /// * `html` - the html to parse
+    ///
+    /// # Returns
+    /// `Result<Value, ExtractError>`, where the error is `ExtractError::MissingRequired` with the dotted paths
+    /// (starting with the root config name) of all missing required configs.
+    ///
+    /// # Examples
+    /// ```
+    /// use dom_finder::{Config, ExtractError, Finder};
+    /// let cfg_yml: &str = r"
+    /// name: root
+    /// base_path: html
+    /// children:
+    ///   - name: title
+    ///     base_path: h1
+    ///     extract: text
+    ///     required: true
+    /// ";
+    /// let cfg = Config::from_yaml(cfg_yml).unwrap();
+    /// let finder = Finder::new(&cfg).unwrap();
+    /// let res = finder.try_parse("<html><body><h2>Title</h2></body></html>");
+    /// assert!(matches!(res, Err(ExtractError::MissingRequired(paths)) if paths == ["root.title"]));
+    /// ```
+    pub fn try_parse(&self, html: &str) -> Result<Value, ExtractError> {
+        let doc = self.build_document(html);
+        let sel = Selection::from(doc.root());
+        let ctx = ParseContext::from_root(&sel);
+        let val = self.parse_value_with(&sel, &ctx);
+        let missing = ctx.missing.into_inner();
+        if !missing.is_empty() {
+            return Err(ExtractError::MissingRequired(missing));
+        }
+        Ok(self.wrap_result(val))
+    }
+
+    /// Parses the given html like `Finder::parse`, but also returns the number of matched nodes for every config.
+    /// It is useful for monitoring: a count that drops to zero means the selector does not match anymore.
+    /// # Arguments
+    /// * `html` - the html to parse
+    ///
+    /// # Returns
+    /// A tuple of the parsed `Value` and a flat `Value::Object`, which maps the dotted config paths
+    /// (starting with the root config name, i.e. `root.results.url`) to the number of matched nodes.
+    /// A count of a descendant config is summed over all matched elements of its parent.
+    /// A config without `many` matches at most one node per parent element.
+    /// Configs that were not reached (i.e. their parent's selection is missing) have the count `0`.
+    ///
+    /// # Examples
+    /// ```
+    /// use dom_finder::{Config, Finder, Value};
+    /// let cfg_yml: &str = r"
+    /// name: root
+    /// base_path: html
+    /// children:
+    ///   - name: links
+    ///     base_path: a[href]
+    ///     many: true
+    ///     extract: href
+    /// ";
+    /// let cfg = Config::from_yaml(cfg_yml).unwrap();
+    /// let finder = Finder::new(&cfg).unwrap();
+    /// let html = r#"<html><body><a href="/1">1</a><a href="/2">2</a></body></html>"#;
+    /// let (_, counts) = finder.parse_with_counts(html);
+    /// let Value::Object(counts) = counts else { unreachable!() };
+    /// assert_eq!(counts.get("root.links"), Some(&Value::Int(2)));
+    /// ```
+    pub fn parse_with_counts(&self, html: &str) -> (Value, Value) {
+        let doc = self.build_document(html);
+        let sel = Selection::from(doc.root());
+        let ctx = ParseContext {
+            counts: Some(RefCell::default()),
+            ..ParseContext::from_root(&sel)
+        };
+        let val = self.wrap_result(self.parse_value_with(&sel, &ctx));
+
+        let counts = ctx.counts.unwrap_or_default().into_inner();
+        let mut counts_map = InnerMap::default();
+        self.collect_counts(&counts, &mut counts_map);
+        (val, Value::Object(counts_map))
+    }
+
+    /// Puts the counts of the config and its descendants into the map, in the order of the configs.
+    fn collect_counts(&self, counts: &HashMap<String, usize>, m: &mut InnerMap) {
+        let count = counts.get(self.path.as_ref()).copied().unwrap_or_default();
+        m.insert(self.path.to_string(), Value::Int(count as i64));
+        let variant_children = self.variants.iter().flat_map(|v| v.children.iter());
+        for child in self.children.iter().chain(variant_children) {
+            child.collect_counts(counts, m);
+        }
+    }
+
+    /// Parses the given html like `Finder::parse`, but only if its size does not exceed `max_bytes`.
+    /// The size is checked before building the document, so it is a cheap guard against huge inputs.
+    /// # Arguments
+    /// * `html` - the html to parse
+    /// * `max_bytes` - the maximum allowed size of the html in bytes
+    ///
+    /// # Returns
+    /// `Result<Value, ParseError>`, where the error is `ParseError::InputTooLarge` if the html exceeds the limit.
+    pub fn parse_limited(&self, html: &str, max_bytes: usize) -> Result<Value, ParseError> {
+        if html.len() > max_bytes {
+            return Err(ParseError::InputTooLarge(html.len(), max_bytes));
+        }
+        Ok(self.parse(html))
+    }
+
+    /// Parses the given html like `Finder::parse`, but also returns the warnings collected during parsing.
+    /// Currently, a warning is recorded for every panic caught in a config with the `isolate_panics` option.
+    /// # Arguments
+    /// * `html` - the html to parse
+    ///
+    /// # Returns
+    /// A tuple of the parsed `Value` and the list of warnings.
+    pub fn parse_with_warnings(&self, html: &str) -> (Value, Vec<String>) {
+        let doc = self.build_document(html);
+        let sel = Selection::from(doc.root());
+        let ctx = ParseContext::from_root(&sel);
+        let val = self.parse_value_with(&sel, &ctx);
+        (self.wrap_result(val), ctx.warnings.into_inner())
+    }
+
+    fn parse_value_with(&self, root: &Selection, ctx: &ParseContext) -> Value {
+        let v = if self.isolate_panics {
+            self.parse_selection_isolated(root, ctx)
+        } else {
+            self.parse_selection(root, ctx)
+        };
+        match self.default_value {
+            Some(ref default_value) if is_blank(&v) => {
+                cast_value(default_value.to_string(), self.cast, self.strict_cast)
+            }
+            _ => v,
+        }
+    }
+
+    /// Parses the selection like `parse_selection`, but a panic is caught and recorded as a warning,
+    /// in that case the result is `Value::Null`.
+    fn parse_selection_isolated(&self, root: &Selection, ctx: &ParseContext) -> Value {
+        let res = panic::catch_unwind(AssertUnwindSafe(|| self.parse_selection(root, ctx)));
+        res.unwrap_or_else(|payload| {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            ctx.warnings
+                .borrow_mut()
+                .push(format!("`{}`: extraction panicked: {}", self.name, reason));
+            Value::Null
+        })
+    }
+
+    /// Selects the matching nodes from the given root selection according to the finder's options.
+    fn select<'b>(&self, root: &Selection<'b>) -> Selection<'b> {
+        let sel = self.select_nodes(root);
+        if !self.many || (self.skip == 0 && self.limit.is_none()) {
+            return sel;
+        }
+        let nodes = sel.nodes().iter().skip(self.skip);
+        match self.limit {
+            Some(limit) => Selection::from(nodes.take(limit).cloned().collect::<Vec<_>>()),
+            None => Selection::from(nodes.cloned().collect::<Vec<_>>()),
+        }
+    }
+
+    fn select_nodes<'b>(&self, root: &Selection<'b>) -> Selection<'b> {
+        if self.inherit {
+            self.filter_selection(root.clone())
         } else if self.parent {
-            root.select_matcher(self.get_matcher()).parent()
+            self.filter_selection(self.select_path(root).parent())
         } else if self.many {
-            root.select_matcher(self.get_matcher())
+            self.filter_selection(self.select_path(root))
+        } else if self.has_node_filters()
+            || !self.split_matchers.is_empty()
+            || self.ci_matcher.is_some()
+        {
+            self.filter_selection(self.select_path(root)).first()
         } else {
             root.select_single_matcher(self.get_matcher())
+        }
+    }
+
+    fn parse_selection(&self, root: &Selection, ctx: &ParseContext) -> Value {
+        let sel: Selection = self.select(root);
+
+        if !sel.exists() {
+            if self.required {
+                let mut missing = ctx.missing.borrow_mut();
+                if !missing.iter().any(|p| *p == self.path) {
+                    missing.push(self.path.to_string());
+                }
+            }
+            return if self.is_count() {
+                Value::Int(0)
+            } else {
+                Value::Null
+            };
+        }
+        if let Some(ref counts) = ctx.counts {
+            let mut counts = counts.borrow_mut();
+            match counts.get_mut(self.path.as_ref()) {
+                Some(count) => *count += sel.length(),
+                None => {
+                    counts.insert(self.path.to_string(), sel.length());
+                }
+            }
+        }
+
+        let has_children = self.has_children();
+
+        let mut v = match (has_children, self.many) {
+            (false, true) if self.is_count() => Value::Int(sel.length() as i64),
+            _ if self.table && self.many => sel
+                .iter()
+                .take_while(|_| !ctx.is_expired())
+                .map(|item| self.parse_table(&item, ctx))
+                .collect(),
+            _ if self.table => self.parse_table(&sel.first(), ctx),
+            (true, true) if self.as_tuple => self.parse_children_to_slice_tuples(&sel, ctx),
+            (true, false) if self.as_tuple => self.parse_children_to_tuple(&sel, ctx),
+            (true, true) => self.parse_children_to_slice_maps(&sel, ctx),
+            (true, false) => self.parse_children_to_map(&sel, ctx),
+            (false, true) => {
+                let mut tmp_res: Vec<PipelineOutput> = Vec::new();
+                self.produce_outputs(&sel, ctx, &mut |out| tmp_res.push(out));
+                let tmp_res = tmp_res.into_iter();
+
+                if !self.join_sep.is_empty() {
+                    // `join_sep` is incompatible with terminal procs, so all outputs are scalars
+                    let tmp_res: Vec<String> = tmp_res
+                        .filter_map(|it| match it {
+                            PipelineOutput::Scalar(s) => Some(s),
+                            PipelineOutput::Structured(_) => None,
+                        })
+                        .collect();
+                    Value::from(tmp_res.join(&self.join_sep))
+                } else {
+                    Value::from_iter(tmp_res.map(|it| cast_output(it, self.cast, self.strict_cast)))
+                }
+            }
+            _ => {
+                let item = sel.first();
+                if let Some(tmp_val) = self.handle_selection(&item, ctx) {
+                    cast_output(tmp_val, self.cast, self.strict_cast)
+                } else {
+                    Value::Null
+                }
+            }
+        };
+
+        // tables and tuples have no `enumerate` and `join_sep`, so they are reversed as the result array
+        if self.reverse && self.many && (self.table || self.as_tuple) {
+            if let Value::Array(ref mut items) = v {
+                items.reverse();
+            }
+        }
+
+        if self.remove_selection {
+            sel.remove();
+        }
+        v
+    }
+
+    /// Selects all nodes matching the `base_path`. If `split_path` is set, the nodes matched by every part
+    /// of the path are joined in the order of the parts, the nodes that were already matched are skipped.
+    /// If nothing is matched and `ci_class` is set, the selection is retried with case-insensitive class and id selectors.
+    fn select_path<'b>(&self, root: &Selection<'b>) -> Selection<'b> {
+        let sel = self.select_path_strict(root);
+        match self.ci_matcher {
+            Some(ref ci_matcher) if !sel.exists() => root.select_matcher(ci_matcher),
+            _ => sel,
+        }
+    }
+
+    fn select_path_strict<'b>(&self, root: &Selection<'b>) -> Selection<'b> {
+        if self.split_matchers.is_empty() {
+            return root.select_matcher(self.get_matcher());
+        }
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        let mut nodes = Vec::new();
+        for matcher in self.split_matchers.iter() {
+            for node in root.select_matcher(matcher).nodes() {
+                if seen.insert(node.id) {
+                    nodes.push(node.clone());
+                }
+            }
+        }
+        Selection::from(nodes)
+    }
+
+    /// Returns `true` if the finder has options that require checking every matched node.
+    fn has_node_filters(&self) -> bool {
+        self.filter.is_some() || self.skip_hidden || self.role.is_some()
+    }
+
+    /// Keeps only the nodes of the selection that satisfy the `filter`, `skip_hidden` and `role` options.
+    fn filter_selection<'b>(&self, sel: Selection<'b>) -> Selection<'b> {
+        if !self.has_node_filters() {
+            return sel;
+        }
+        let nodes: Vec<_> = sel
+            .nodes()
+            .iter()
+            .filter(|node| self.is_node_allowed(node))
+            .cloned()
+            .collect();
+        Selection::from(nodes)
+    }
+
+    fn is_node_allowed(&self, node: &NodeRef) -> bool {
+        if self.skip_hidden && is_hidden(node) {
+            return false;
+        }
+        if let Some(ref role) = self.role {
+            if !has_role(node, role) {
+                return false;
+            }
+        }
+        match self.filter {
+            Some((ref attr, ref re)) => matches!(node.attr(attr), Some(val) if re.is_match(&val)),
+            None => true,
+        }
+    }
+
+    /// Handles the result selection according to the extract type and the pipeline
+    fn handle_selection(&self, sel: &Selection, ctx: &ParseContext) -> Option<PipelineOutput> {
+        if is_structured_extract(&self.extract) {
+            // a structured value is not passed through the pipeline
+            return extract_structured(sel, &self.extract).map(PipelineOutput::Structured);
+        }
+        extract_data(sel, &self.extract).map(|extracted| {
+            if self.decode_entities && is_attr_extract(&self.extract) {
+                let decoded = html_escape::decode_html_entities(&extracted).to_string();
+                self.handle_text(decoded, ctx)
+            } else if self.trim_html && is_html_extract(&self.extract) {
+                self.handle_text(extracted.trim().to_string(), ctx)
+            } else {
+                self.handle_text(extracted.to_string(), ctx)
+            }
+        })
+    }
+
+    /// Applies the pipeline (if there is one) to the extracted text
+    fn handle_text(&self, text: String, ctx: &ParseContext) -> PipelineOutput {
+        if let Some(ref pipeline) = self.pipeline {
+            pipeline.handle_with_base(text, ctx.base_url.as_deref())
+        } else {
+            PipelineOutput::Scalar(text)
+        }
+    }
+
+    /// Parses the `<table>` element into an array of objects keyed by the header cells text.
+    fn parse_table(&self, table: &Selection, ctx: &ParseContext) -> Value {
+        let mut keys: Vec<String> = Vec::new();
+        let mut rows: Vec<Value> = Vec::new();
+        for row in table.select("tr").nodes() {
+            let cells = table_cells(row);
+            if keys.is_empty() {
+                if !cells.is_empty() && cells.iter().all(|cell| cell.is_header) {
+                    for cell in cells {
+                        let key = collapse_whitespace(&cell.node.text());
+                        keys.extend(std::iter::repeat(key).take(cell.colspan));
+                    }
+                }
+                continue;
+            }
+            if cells.iter().all(|cell| cell.is_header) {
+                continue;
+            }
+            let mut m = InnerMap::default();
+            let mut col = 0;
+            for cell in cells {
+                let Some(key) = keys.get(col) else {
+                    break;
+                };
+                col += cell.colspan;
+                if m.contains_key(key) {
+                    continue;
+                }
+                let text = collapse_whitespace(&cell.node.text());
+                m.insert(
+                    key.clone(),
+                    cast_output(self.handle_text(text, ctx), self.cast, self.strict_cast),
+                );
+            }
+            if !m.is_empty() {
+                rows.push(Value::Object(m));
+            }
+        }
+        Value::Array(rows)
+    }
+
+    /// Returns `true` if the finder counts the matched elements (`extract: count`).
+    fn is_count(&self) -> bool {
+        self.many && self.extract == EXTRACT_COUNT
+    }
+
+    /// Returns `true` if the finder has descendant configs, either `children` or `variants`.
+    fn has_children(&self) -> bool {
+        !self.children.is_empty() || !self.variants.is_empty()
+    }
+
+    /// Returns the descendant configs for the element: the ones of the first matching variant or `children`.
+    fn children_for(&self, element: &Selection) -> &[Finder<'a>] {
+        self.variants
+            .iter()
+            .find(|v| element.is_matcher(&v.matcher))
+            .map_or(&self.children, |v| &v.children)
+    }
+
+    fn parse_children_to_map(&self, element: &Selection, ctx: &ParseContext) -> Value {
+        let mut m = InnerMap::default();
+        let mut has_values = false;
+        for inline in self.children_for(element).iter() {
+            if ctx.is_expired() {
+                break;
+            }
+            let v = inline.parse_value_with(element, ctx);
+            if v.is_empty() {
+                // a zero count is kept, but like `null` it doesn't make the map non-empty
+                if inline.emit_null || inline.is_count() {
+                    m.insert(inline.name.to_string(), v);
+                }
+                continue;
+            }
+            has_values = true;
+
+            if inline.flatten {
+                if let Value::Object(in_map) = v {
+                    for (k, val) in in_map {
+                        m.insert(k, val);
+                    }
+                } else {
+                    m.insert(inline.name.to_string(), v);
+                }
+            } else {
+                m.insert(inline.name.to_string(), v);
+            }
+
+            if self.first_occurrence {
+                break;
+            }
+        }
+        if !has_values {
+            m.clear();
+        }
+        self.insert_id(&mut m);
+        self.handle_object(m)
+    }
+
+    /// Wraps the parsed value into an object under the finder's `name` and applies the `final_pipeline`, if there is one.
+    fn wrap_result(&self, val: Value) -> Value {
+        let mut m: InnerMap = InnerMap::default();
+        m.insert(self.name.to_string(), val);
+        let Some(ref final_pipeline) = self.final_pipeline else {
+            return Value::Object(m);
         };
+        let json = Value::Object(m).to_json_string_with(SerializeOptions::default());
+        match final_pipeline.handle(json) {
+            PipelineOutput::Scalar(s) => value_from_json(&s).unwrap_or(Value::Null),
+            PipelineOutput::Structured(v) => v,
+        }
+    }
 
-        if !sel.exists() {
-            return Value::Null;
+    /// Applies the `post_pipeline` to the assembled object, if there is one.
+    fn handle_object(&self, m: InnerMap) -> Value {
+        let Some(ref post_pipeline) = self.post_pipeline else {
+            return Value::Object(m);
+        };
+        let json = Value::Object(m).to_json_string_with(SerializeOptions::default());
+        match post_pipeline.handle(json) {
+            PipelineOutput::Scalar(s) if self.post_pipeline_json => {
+                value_from_json(&s).unwrap_or(Value::Null)
+            }
+            PipelineOutput::Scalar(s) => Value::String(s),
+            PipelineOutput::Structured(v) => v,
         }
+    }
 
-        let has_children = !self.children.is_empty();
+    fn parse_children_to_slice_maps(&self, selection: &Selection, ctx: &ParseContext) -> Value {
+        let mut values: Vec<InnerMap> = Vec::new();
+        self.produce_maps(selection, ctx, &mut |_, m| values.push(m));
+        for (i, item) in values.iter_mut().enumerate() {
+            self.finish_map(item, i);
+        }
+        if let Some(ref key) = self.with_total {
+            let total = values.len() as i64;
+            for item in values.iter_mut() {
+                item.insert(key.to_string(), Value::Int(total));
+            }
+        }
 
-        let v = match (has_children, self.many) {
-            (true, true) => self.parse_children_to_slice_maps(&sel),
-            (true, false) => self.parse_children_to_map(&sel),
-            (false, true) => {
-                let tmp_res: Vec<String> = sel
-                    .iter()
-                    .filter_map(|item| self.handle_selection(&item))
-                    .collect();
+        Value::from_iter(values.into_iter().map(|m| self.handle_object(m)))
+    }
 
-                if !self.join_sep.is_empty() {
-                    Value::from(tmp_res.join(&self.join_sep))
-                } else {
-                    Value::from_iter(tmp_res.into_iter().map(|it| cast_value(it, self.cast)))
+    /// Produces the result objects of the `many` selection with descendant configs and passes them to the callback
+    /// together with the group of elements they are parsed from. Empty objects are skipped (or stop the production
+    /// if `stop_on_empty` is set), then `keep_where`, `unique` and `reverse` are applied.
+    /// `rename_keys` and `enumerate` are not applied, see `finish_map`.
+    ///
+    /// It is shared by `Finder::parse`, `Finder::parse_field` and `Finder::parse_each`, so they produce the same items.
+    /// Only `reverse` requires to keep all objects before the first callback invocation.
+    fn produce_maps<'b>(
+        &self,
+        selection: &Selection<'b>,
+        ctx: &ParseContext,
+        f: &mut dyn FnMut(&[Selection<'b>], InnerMap),
+    ) {
+        let mut reversed: Vec<(Vec<Selection<'b>>, InnerMap)> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        for group in self.group_selection(selection) {
+            if ctx.is_expired() {
+                break;
+            }
+            let m = self.parse_group_to_map(&group, ctx);
+            if m.is_empty() {
+                if self.stop_on_empty {
+                    break;
                 }
+                continue;
             }
-            _ => {
-                let item = sel.first();
-                if let Some(tmp_val) = self.handle_selection(&item) {
-                    cast_value(tmp_val, self.cast)
-                } else {
-                    Value::Null
+            if let Some(ref keep_where) = self.keep_where {
+                if !keep_where.is_match(&m) {
+                    continue;
                 }
             }
-        };
-
-        if self.remove_selection {
-            sel.remove();
+            if self.unique {
+                let key = Value::Object(m.clone()).to_json_string_with(SerializeOptions::default());
+                if !seen.insert(key) {
+                    continue;
+                }
+            }
+            if self.reverse {
+                reversed.push((group, m));
+            } else {
+                f(&group, m);
+            }
+        }
+        for (group, m) in reversed.into_iter().rev() {
+            f(&group, m);
         }
-        v
     }
 
-    /// Handles the result selection according to the extract type and the pipeline
-    fn handle_selection(&self, sel: &Selection) -> Option<String> {
-        extract_data(sel, &self.extract).map(|extracted| {
-            let extracted = extracted.to_string();
-            if let Some(ref pipeline) = self.pipeline {
-                pipeline.handle(extracted)
+    /// Produces the extracted values of the `many` selection without descendant configs and passes them to the callback,
+    /// `unique` and `reverse` are applied. Values are not casted.
+    fn produce_outputs(
+        &self,
+        selection: &Selection,
+        ctx: &ParseContext,
+        f: &mut dyn FnMut(PipelineOutput),
+    ) {
+        let mut reversed: Vec<PipelineOutput> = Vec::new();
+        let mut seen: HashSet<(bool, String)> = HashSet::new();
+        for item in selection.iter() {
+            if ctx.is_expired() {
+                break;
+            }
+            let Some(out) = self.handle_selection(&item, ctx) else {
+                continue;
+            };
+            if self.unique && !seen.insert(unique_key(&out)) {
+                continue;
+            }
+            if self.reverse {
+                reversed.push(out);
             } else {
-                extracted
+                f(out);
             }
-        })
+        }
+        for out in reversed.into_iter().rev() {
+            f(out);
+        }
     }
 
-    fn parse_children_to_map(&self, element: &Selection) -> Value {
-        let mut m = InnerMap::default();
-        for inline in self.children.iter() {
-            let v = inline.parse_value(element);
+    /// Applies `rename_keys` and `enumerate` to the result object, the `index` is its position among the produced objects.
+    fn finish_map(&self, m: &mut InnerMap, index: usize) {
+        self.rename_map_keys(m);
+        if self.enumerate {
+            m.insert(INDEX_FIELD.to_string(), Value::Int(index as i64));
+        }
+    }
+
+    /// Splits the selection into groups of elements, one group per result object.
+    /// Every group is handled by all descendant configs, unless `group_by` is set,
+    /// then the N-th descendant config handles only the N-th element of the group.
+    fn group_selection<'b>(&self, selection: &Selection<'b>) -> Vec<Vec<Selection<'b>>> {
+        if self.group_by > 0 {
+            selection
+                .nodes()
+                .chunks(self.group_by)
+                .map(|chunk| chunk.iter().cloned().map(Selection::from).collect())
+                .collect()
+        } else {
+            selection.iter().map(|item| vec![item]).collect()
+        }
+    }
+
+    /// Parses the group of elements with the descendant configs into a map.
+    fn parse_group_to_map(&self, group: &[Selection], ctx: &ParseContext) -> InnerMap {
+        let mut m: InnerMap = InnerMap::default();
+        let mut has_values = false;
+        // `group_by` can not be used with `variants`, so the first element of the group chooses the descendant configs
+        let children = match group.first() {
+            Some(first) => self.children_for(first),
+            None => &self.children,
+        };
+        for (i, inline) in children.iter().enumerate() {
+            let pos = if self.group_by > 0 { i } else { 0 };
+            let Some(item) = group.get(pos) else {
+                break;
+            };
+            let v = inline.parse_value_with(item, ctx);
             if v.is_empty() {
+                if inline.emit_null || inline.is_count() {
+                    m.insert(inline.name.to_string(), v);
+                }
                 continue;
             }
+            has_values = true;
 
             if inline.flatten {
-                if let Value::Object(in_map) = v {
-                    for (k, val) in in_map {
-                        m.insert(k, val);
+                if let Value::Object(obj) = v {
+                    for (key, val) in obj {
+                        // push flat maps right in the result values
+                        m.insert(key, val);
                     }
                 } else {
                     m.insert(inline.name.to_string(), v);
@@ -243,47 +1471,85 @@ impl<'a> Finder<'a> {
                 break;
             }
         }
-        Value::Object(m)
+        if !has_values {
+            m.clear();
+        }
+        self.insert_id(&mut m);
+        // an empty object stays empty, so it is skipped as usual
+        if let (Some(meta_key), false) = (self.meta_key.as_ref(), m.is_empty()) {
+            if let Some(node) = group.first().and_then(|sel| sel.nodes().first()) {
+                m.insert(meta_key.to_string(), node_meta(node));
+            }
+        }
+        if let (Some((_, matcher)), false) = (self.section_heading.as_ref(), m.is_empty()) {
+            let heading = group
+                .first()
+                .and_then(|sel| sel.nodes().first())
+                .and_then(|node| preceding_match(node, matcher));
+            if let Some(heading) = heading {
+                let text = collapse_whitespace(&heading.text());
+                m.insert(SECTION_FIELD.to_string(), Value::String(text));
+            }
+        }
+        m
     }
 
-    fn parse_children_to_slice_maps(&self, selection: &Selection) -> Value {
-        let mut values: Vec<InnerMap> = Vec::new();
-        for item in selection.iter() {
-            let mut m: InnerMap = InnerMap::default();
-            for inline in self.children.iter() {
-                let v = inline.parse_value(&item);
-                if v.is_empty() {
-                    continue;
-                }
+    /// Inserts the stable id of the object, computed from the values of the `id_from` fields.
+    /// An empty object stays empty.
+    fn insert_id(&self, m: &mut InnerMap) {
+        let Some(ref id_key) = self.id_key else {
+            return;
+        };
+        if m.is_empty() {
+            return;
+        }
+        let values: Value = self
+            .id_from
+            .iter()
+            .map(|field| m.get(field).cloned().unwrap_or(Value::Null))
+            .collect();
+        let json = values.to_json_string_with(SerializeOptions::default());
+        let id = short_hash(&json, SHORT_HASH_DEFAULT_LEN);
+        m.insert(id_key.to_string(), Value::String(id));
+    }
 
-                if inline.flatten {
-                    if let Value::Object(obj) = v {
-                        for (key, val) in obj {
-                            // push flat maps right in the result values
-                            m.insert(key, val);
-                        }
-                    } else {
-                        m.insert(inline.name.to_string(), v);
-                    }
-                } else {
-                    m.insert(inline.name.to_string(), v);
+    /// Renames the keys of the map according to the `rename_keys` option.
+    /// All keys are renamed at once, so renames can be swapped or chained (`a: b, b: c`).
+    fn rename_map_keys(&self, item: &mut InnerMap) {
+        let mut renamed = InnerMap::with_capacity_and_hasher(item.len(), Default::default());
+        for (key, val) in item.drain(..) {
+            match self.rename_keys.get(&key) {
+                // a renamed value replaces the value of the key it collides with
+                Some(new_key) => {
+                    renamed.insert(new_key.clone(), val);
                 }
-
-                if self.first_occurrence {
-                    break;
+                None => {
+                    renamed.entry(key).or_insert(val);
                 }
             }
-            if !m.is_empty() {
-                values.push(m);
-            }
         }
-        if self.enumerate {
-            for (i, item) in values.iter_mut().enumerate() {
-                item.insert(INDEX_FIELD.to_string(), Value::Int(i as i64));
-            }
+        *item = renamed;
+    }
+
+    fn parse_children_to_tuple(&self, element: &Selection, ctx: &ParseContext) -> Value {
+        let values: Vec<Value> = self
+            .children
+            .iter()
+            .map(|inline| inline.parse_value_with(element, ctx))
+            .collect();
+        if values.iter().all(|v| v.is_empty()) {
+            return Value::Null;
         }
+        Value::Array(values)
+    }
 
-        Value::from_iter(values.into_iter().map(Value::Object))
+    fn parse_children_to_slice_tuples(&self, selection: &Selection, ctx: &ParseContext) -> Value {
+        selection
+            .iter()
+            .take_while(|_| !ctx.is_expired())
+            .map(|item| self.parse_children_to_tuple(&item, ctx))
+            .filter(|v| !v.is_empty())
+            .collect()
     }
 }
 
@@ -292,11 +1558,14 @@ impl<'a> Finder<'a> {
 /// - bool - casts the value to bool, if the value is empty it is `false`, otherwise it is `true`.
 /// - int - casts the value to int
 /// - float - casts the value to float
+/// - json - parses the value as JSON
+/// - int_array, float_array - splits the value by commas into an array of numbers
 /// - string - casts the value to string
 /// # Arguments
 /// * `s` - `String`, the value to cast
 /// * `cast` - `CastType`, the type to cast to
-fn cast_value(s: String, cast: CastType) -> Value {
+/// * `strict` - if it is `true`, then a failed int or float cast gives `Value::Null`, otherwise it gives `0`
+fn cast_value(s: String, cast: CastType, strict: bool) -> Value {
     match cast {
         CastType::Bool => {
             let mut x: bool = false;
@@ -305,12 +1574,260 @@ fn cast_value(s: String, cast: CastType) -> Value {
             }
             Value::from(x)
         }
-        CastType::Int => Value::from(s.parse::<i64>().unwrap_or(0)),
-        CastType::Float => Value::from(s.parse::<f64>().unwrap_or(0.0)),
+        CastType::Int => match s.parse::<i64>() {
+            Ok(val) => Value::from(val),
+            Err(_) if strict => Value::Null,
+            Err(_) => Value::from(0),
+        },
+        CastType::Float => match s.parse::<f64>() {
+            Ok(val) => Value::from(val),
+            Err(_) if strict => Value::Null,
+            Err(_) => Value::from(0.0),
+        },
+        CastType::Json => value_from_json(&s).unwrap_or(Value::Null),
+        CastType::IntArray => s
+            .split(',')
+            .filter_map(|item| item.trim().parse::<i64>().ok())
+            .map(Value::Int)
+            .collect(),
+        CastType::FloatArray => s
+            .split(',')
+            .filter_map(|item| item.trim().parse::<f64>().ok())
+            .map(Value::Float)
+            .collect(),
         _ => Value::from(s),
     }
 }
 
+/// Represents a cell of the table row.
+struct TableCell<'b> {
+    node: NodeRef<'b>,
+    is_header: bool,
+    colspan: usize,
+}
+
+/// Returns the `<td>` and `<th>` cells of the table row.
+fn table_cells<'b>(row: &NodeRef<'b>) -> Vec<TableCell<'b>> {
+    row.element_children()
+        .into_iter()
+        .filter_map(|node| {
+            let is_header = match node.node_name()?.as_ref() {
+                "th" => true,
+                "td" => false,
+                _ => return None,
+            };
+            let colspan = node
+                .attr("colspan")
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(1)
+                .max(1);
+            Some(TableCell {
+                node,
+                is_header,
+                colspan,
+            })
+        })
+        .collect()
+}
+
+/// Replaces every run of whitespace characters with a single space and trims the text.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Returns the metadata of the node: the tag name and the `id` and `class` attributes, if they are present.
+fn node_meta(node: &NodeRef) -> Value {
+    let mut meta = InnerMap::default();
+    if let Some(tag) = node.node_name() {
+        meta.insert("tag".to_string(), Value::from(tag.as_ref()));
+    }
+    for attr in ["id", "class"] {
+        if let Some(val) = node.attr(attr) {
+            meta.insert(attr.to_string(), Value::from(val.as_ref()));
+        }
+    }
+    Value::Object(meta)
+}
+
+/// Returns the nearest element that matches the matcher and precedes the node in the document order:
+/// it walks back through the previous siblings (and their descendants), then up to the parent, and so on.
+/// An ancestor of the node is considered preceding.
+fn preceding_match<'b>(node: &NodeRef<'b>, matcher: &Matcher) -> Option<NodeRef<'b>> {
+    let is_match = |n: &NodeRef| n.is_element() && Selection::from(n.clone()).is_matcher(matcher);
+    let mut current = node.clone();
+    loop {
+        while let Some(prev) = current.prev_sibling() {
+            if prev.is_element() {
+                // descendants follow the element in the document order, so the last matching descendant is the nearest
+                let found = Selection::from(prev.clone()).select_matcher(matcher);
+                if let Some(found) = found.nodes().last() {
+                    return Some(found.clone());
+                }
+                if is_match(&prev) {
+                    return Some(prev);
+                }
+            }
+            current = prev;
+        }
+        let parent = current.parent()?;
+        if is_match(&parent) {
+            return Some(parent);
+        }
+        current = parent;
+    }
+}
+
+/// Checks if the node or any of its ancestors is hidden with the inline `style` attribute,
+/// i.e. it contains `display: none` or `visibility: hidden`.
+fn is_hidden(node: &NodeRef) -> bool {
+    std::iter::once(node.clone())
+        .chain(node.ancestors_it(None))
+        .any(|n| match n.attr("style") {
+            Some(style) => {
+                let style: String = style
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .flat_map(char::to_lowercase)
+                    .collect();
+                style.contains("display:none") || style.contains("visibility:hidden")
+            }
+            None => false,
+        })
+}
+
+/// Checks if the node has the given (lowercased) ARIA role. Explicit roles from the `role` attribute
+/// take precedence over the implicit role of the element.
+fn has_role(node: &NodeRef, role: &str) -> bool {
+    if let Some(explicit) = node.attr("role") {
+        if !explicit.trim().is_empty() {
+            return explicit
+                .split_whitespace()
+                .any(|r| r.eq_ignore_ascii_case(role));
+        }
+    }
+    implicit_role(node) == Some(role)
+}
+
+/// Returns the implicit ARIA role of the common elements.
+fn implicit_role(node: &NodeRef) -> Option<&'static str> {
+    let name = node.node_name()?.to_ascii_lowercase();
+    let role = match name.as_str() {
+        "a" | "area" if node.has_attr("href") => "link",
+        "article" => "article",
+        "aside" => "complementary",
+        "button" => "button",
+        "dialog" => "dialog",
+        "footer" => "contentinfo",
+        "form" => "form",
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+        "header" => "banner",
+        "hr" => "separator",
+        "img" if matches!(node.attr("alt"), Some(ref alt) if alt.is_empty()) => "presentation",
+        "img" => "img",
+        "input" => {
+            let kind = node.attr("type").map(|t| t.to_ascii_lowercase());
+            match kind.as_deref().unwrap_or("text") {
+                "button" | "image" | "reset" | "submit" => "button",
+                "checkbox" => "checkbox",
+                "radio" => "radio",
+                "range" => "slider",
+                "number" => "spinbutton",
+                "search" => "searchbox",
+                "email" | "tel" | "text" | "url" => "textbox",
+                _ => return None,
+            }
+        }
+        "li" => "listitem",
+        "main" => "main",
+        "nav" => "navigation",
+        "ol" | "ul" | "menu" => "list",
+        "option" => "option",
+        "progress" => "progressbar",
+        "select" if node.has_attr("multiple") => "listbox",
+        "select" => "combobox",
+        "table" => "table",
+        "td" => "cell",
+        "textarea" => "textbox",
+        "th" => "columnheader",
+        "tr" => "row",
+        _ => return None,
+    };
+    Some(role)
+}
+
+/// Rejects the combinations of options where the `cast` or `join_sep` options would be silently ignored.
+fn validate_output(config: &Config, pipeline: Option<&Pipeline>) -> Result<(), ParseError> {
+    let terminal = pipeline.and_then(|p| p.terminal());
+    let is_structured = is_structured_extract(&config.extract);
+    let is_count = config.extract == EXTRACT_COUNT;
+    // structured values and counts are not passed through the pipeline
+    if pipeline.is_some() && (is_structured || is_count) {
+        return Err(ParseError::IncompatiblePipeline(
+            config.name.clone(),
+            format!("`{}` extract type", config.extract),
+        ));
+    }
+    if config.many && !config.join_sep.is_empty() {
+        if let Some(terminal) = terminal {
+            return Err(ParseError::IncompatibleJoin(
+                config.name.clone(),
+                format!("`{}` pipeline proc", terminal.name()),
+            ));
+        }
+        if is_structured || is_count {
+            return Err(ParseError::IncompatibleJoin(
+                config.name.clone(),
+                format!("`{}` extract type", config.extract),
+            ));
+        }
+    }
+
+    if matches!(config.cast, CastType::String) {
+        return Ok(());
+    }
+    let reason = if !config.children.is_empty() || !config.variants.is_empty() {
+        "`children` option".to_string()
+    } else if config.many && !config.join_sep.is_empty() {
+        "`join_sep` option, joined result is always a string".to_string()
+    } else if let Some(terminal) = terminal {
+        format!(
+            "`{}` pipeline proc, it produces a structured value",
+            terminal.name()
+        )
+    } else if is_structured {
+        format!(
+            "`{}` extract type, it produces a structured value",
+            config.extract
+        )
+    } else if is_count {
+        "`count` extract type, it produces an integer".to_string()
+    } else {
+        return Ok(());
+    };
+    Err(ParseError::IncompatibleCast(config.name.clone(), reason))
+}
+
+/// Checks if the value is `null` or an empty string, array or object.
+/// Unlike `Value::is_empty`, zero numbers and `false` are not considered blank.
+fn is_blank(v: &Value) -> bool {
+    match v {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        _ => false,
+    }
+}
+
+/// Converts the pipeline output into a `Value`.
+/// Only `PipelineOutput::Scalar` is casted, structured values are returned as is.
+fn cast_output(output: PipelineOutput, cast: CastType, strict: bool) -> Value {
+    match output {
+        PipelineOutput::Scalar(s) => cast_value(s, cast, strict),
+        PipelineOutput::Structured(v) => v,
+    }
+}
+
 impl TryFrom<Config> for Finder<'_> {
     type Error = ParseError;
     fn try_from(config: Config) -> Result<Self, Self::Error> {
@@ -318,23 +1835,247 @@ impl TryFrom<Config> for Finder<'_> {
     }
 }
 
+/// Returns the key that identifies the pipeline output for the `unique` option.
+/// Structured values are compared by their JSON representation.
+fn unique_key(output: &PipelineOutput) -> (bool, String) {
+    match output {
+        PipelineOutput::Scalar(s) => (false, s.clone()),
+        PipelineOutput::Structured(v) => (true, v.to_json_string_with(SerializeOptions::default())),
+    }
+}
+
+/// Splits the css selector by top-level commas. Commas inside brackets, parentheses and quotes are not separators.
+/// Empty parts are skipped.
+fn split_selector(selector: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, c) in selector.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                parts.push(&selector[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&selector[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Rewrites the class (`.name`) and id (`#name`) selectors of the css selector into attribute selectors
+/// with the case-insensitive flag, i.e. `div.Result` becomes `div[class~="Result" i]`.
+/// Returns `None` if a class or an id selector contains escapes, which are not supported.
+fn relax_class_selectors(selector: &str) -> Option<String> {
+    let mut res = String::with_capacity(selector.len());
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut chars = selector.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth = depth.saturating_sub(1),
+            (None, '.' | '#') if depth == 0 => {
+                let mut name = String::new();
+                while let Some(n) = chars.next_if(|n| n.is_alphanumeric() || matches!(n, '-' | '_'))
+                {
+                    name.push(n);
+                }
+                if name.is_empty() || chars.peek() == Some(&'\\') {
+                    return None;
+                }
+                let attr = if c == '.' { "class~" } else { "id" };
+                res.push_str(&format!("[{}=\"{}\" i]", attr, name));
+                continue;
+            }
+            _ => {}
+        }
+        res.push(c);
+    }
+    Some(res)
+}
+
+/// Returns `true` if the extract type serializes the html, i.e. `html` or `inner_html`.
+fn is_html_extract(extract_type: &str) -> bool {
+    matches!(extract_type, EXTRACT_HTML | EXTRACT_INNER_HTML)
+}
+
+/// Returns `true` if the extract type is an html-attribute name.
+fn is_attr_extract(extract_type: &str) -> bool {
+    extract_type.starts_with(EXTRACT_ATTR_PREFIX)
+        || !matches!(
+            extract_type,
+            EXTRACT_TEXT
+                | EXTRACT_INNER_TEXT
+                | EXTRACT_IMMEDIATE_TEXT
+                | EXTRACT_HTML
+                | EXTRACT_INNER_HTML
+                | EXTRACT_COMMENT
+                | EXTRACT_ACCESSIBLE_NAME
+                | EXTRACT_SIBLING_INDEX
+                | EXTRACT_TAG_NAME
+                | EXTRACT_ATTRS
+                | EXTRACT_DATA
+                | EXTRACT_CLASS_LIST
+                | EXTRACT_COUNT
+        )
+}
+
 /// Extracts the data from the given selection according to the extract type
 /// The extract type can be one of the following:
 /// - text - extracts the text of the selection
 /// - inner_text - extracts the text of the selection without the text of the children
 /// - html - extracts the html of the selection
 /// - inner_html - extracts the inner html of the selection without it's root node.
+/// - comment - extracts the concatenated contents of the comment nodes inside the selection.
+/// - accessible_name - extracts the accessible name of the first node of the selection.
+/// - sibling_index - extracts the index of the first node of the selection among its element siblings.
+/// - tag_name - extracts the local name of the first node of the selection, i.e. `a`.
+/// - attrs, data, class_list, NAME[] - are not handled here, see `extract_structured`.
+/// - attr:NAME - extracts the attribute with the given name, even if it is a reserved word.
+/// - any other value - extracts the attribute with this name.
 #[inline(always)]
 fn extract_data(sel: &Selection, extract_type: &str) -> Option<StrTendril> {
+    if let Some(attr) = extract_type.strip_prefix(EXTRACT_ATTR_PREFIX) {
+        return sel.attr(attr);
+    }
     match extract_type {
         EXTRACT_TEXT => Some(sel.text()),
         EXTRACT_INNER_TEXT | EXTRACT_IMMEDIATE_TEXT => Some(sel.immediate_text()),
         EXTRACT_HTML => sel.try_html(),
         EXTRACT_INNER_HTML => sel.try_inner_html(),
+        EXTRACT_COMMENT => extract_comments(sel),
+        EXTRACT_ACCESSIBLE_NAME => sel
+            .nodes()
+            .first()
+            .map(|node| StrTendril::from(accessible_name(node))),
+        EXTRACT_SIBLING_INDEX => sel.nodes().first().map(|node| {
+            let mut index = 0;
+            let mut prev = node.prev_element_sibling();
+            while let Some(sibling) = prev {
+                index += 1;
+                prev = sibling.prev_element_sibling();
+            }
+            StrTendril::from(index.to_string())
+        }),
+        EXTRACT_TAG_NAME => sel.nodes().first().and_then(|node| node.node_name()),
         _ => sel.attr(extract_type),
     }
 }
 
+/// Returns `true` if the extract type produces a structured value, i.e. `attrs` or `class[]`.
+fn is_structured_extract(extract_type: &str) -> bool {
+    matches!(
+        extract_type,
+        EXTRACT_ATTRS | EXTRACT_DATA | EXTRACT_CLASS_LIST
+    ) || extract_type.ends_with(EXTRACT_LIST_SUFFIX)
+}
+
+/// Extracts the structured value from the given selection according to the extract type:
+/// - attrs - extracts all attributes of the first node of the selection into an object (name -> value).
+/// - data - extracts the `data-*` attributes of the first node of the selection into an object,
+///   the keys are the attribute names without the `data-` prefix.
+/// - NAME[] - extracts the whitespace-separated tokens of the attribute with the given name into an array.
+/// - class_list - like `class[]`, but gives an empty array if the first node of the selection has no `class` attribute.
+///
+/// Returns `None` if the selection has no element or there is no such attribute.
+fn extract_structured(sel: &Selection, extract_type: &str) -> Option<Value> {
+    if let Some(attr) = extract_type.strip_suffix(EXTRACT_LIST_SUFFIX) {
+        let val = sel.attr(attr.strip_prefix(EXTRACT_ATTR_PREFIX).unwrap_or(attr))?;
+        return Some(val.split_whitespace().map(Value::from).collect());
+    }
+    let node = sel.nodes().first().filter(|node| node.is_element())?;
+    if extract_type == EXTRACT_CLASS_LIST {
+        let classes = node.attr("class").unwrap_or_default();
+        return Some(classes.split_whitespace().map(Value::from).collect());
+    }
+    let prefix = if extract_type == EXTRACT_DATA {
+        DATA_ATTR_PREFIX
+    } else {
+        ""
+    };
+    let attrs: InnerMap = node
+        .attrs()
+        .into_iter()
+        .filter_map(|attr| {
+            let name = attr.name.local.strip_prefix(prefix)?.to_string();
+            Some((name, Value::from(attr.value.as_ref())))
+        })
+        .collect();
+    Some(Value::Object(attrs))
+}
+
+/// Extracts the concatenated contents of all comment nodes inside the selection.
+/// Returns `None` if there are no comments.
+fn extract_comments(sel: &Selection) -> Option<StrTendril> {
+    let mut comments: Option<StrTendril> = None;
+    for node in sel.nodes().iter().flat_map(|node| node.descendants_it()) {
+        let contents = node
+            .query(|n| match n.data {
+                NodeData::Comment { ref contents } => Some(StrTendril::from(contents.as_ref())),
+                _ => None,
+            })
+            .flatten();
+        if let Some(contents) = contents {
+            comments
+                .get_or_insert_with(StrTendril::new)
+                .push_tendril(&contents);
+        }
+    }
+    comments
+}
+
+/// Returns the accessible name of the element, the first non-empty of:
+/// `aria-label`, the text of the elements referenced by `aria-labelledby`, `title` and the text content.
+/// Whitespace in the result is normalized.
+fn accessible_name(node: &NodeRef) -> String {
+    if let Some(label) = node.attr("aria-label") {
+        let label = collapse_whitespace(&label);
+        if !label.is_empty() {
+            return label;
+        }
+    }
+    if let Some(ids) = node.attr("aria-labelledby") {
+        let root = node
+            .ancestors_it(None)
+            .last()
+            .unwrap_or_else(|| node.clone());
+        let label = ids
+            .split_whitespace()
+            .filter_map(|id| {
+                root.descendants_it()
+                    .find(|n| matches!(n.attr("id"), Some(ref val) if val.as_ref() == id))
+            })
+            .map(|n| collapse_whitespace(&n.text()))
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<String>>()
+            .join(" ");
+        if !label.is_empty() {
+            return label;
+        }
+    }
+    if let Some(title) = node.attr("title") {
+        let title = collapse_whitespace(&title);
+        if !title.is_empty() {
+            return title;
+        }
+    }
+    collapse_whitespace(&node.text())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,4 +2156,169 @@ mod tests {
         let finder = Finder::new(&cfg);
         assert!(finder.is_err());
     }
+
+    #[test]
+    fn finder_filter_invalid_regex() {
+        let cfg_yml: &str = r"
+        name: links
+        base_path: a[href]
+        many: true
+        extract: href
+        filter: [href, '^(https']
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+
+        let finder = Finder::new(&cfg);
+        assert!(finder.is_err());
+    }
+
+    #[test]
+    fn finder_pipeline_invalid_argument() {
+        let cfg_yml: &str = r"
+        name: preview
+        base_path: p
+        extract: text
+        pipeline: [[first_words, many]]
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+
+        let finder = Finder::new(&cfg);
+        assert!(finder.is_err());
+    }
+
+    #[test]
+    fn finder_cast_with_join_sep() {
+        let cfg_yml: &str = r"
+        name: prices
+        base_path: span.price
+        extract: text
+        many: true
+        join_sep: ', '
+        cast: float
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+
+        let finder = Finder::new(&cfg);
+        assert!(matches!(finder, Err(ParseError::IncompatibleCast(..))));
+    }
+
+    #[test]
+    fn finder_cast_with_children() {
+        let cfg_yml: &str = r"
+        name: root
+        base_path: html
+        cast: int
+        children:
+          - name: title
+            base_path: h1
+            extract: text
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+
+        let finder = Finder::new(&cfg);
+        assert!(matches!(finder, Err(ParseError::IncompatibleCast(..))));
+    }
+
+    #[test]
+    fn finder_cast_with_terminal_proc() {
+        let cfg_yml: &str = r"
+        name: nutrients
+        base_path: td.nutrients
+        extract: text
+        pipeline: [[numbers_with_units]]
+        cast: int
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+
+        let finder = Finder::new(&cfg);
+        assert!(matches!(finder, Err(ParseError::IncompatibleCast(..))));
+    }
+
+    #[test]
+    fn finder_join_sep_with_terminal_proc() {
+        let cfg_yml: &str = r"
+        name: nutrients
+        base_path: td.nutrients
+        extract: text
+        many: true
+        join_sep: ','
+        pipeline: [[numbers_with_units]]
+        ";
+        let cfg = Config::from_yaml(cfg_yml).unwrap();
+
+        let finder = Finder::new(&cfg);
+        assert!(matches!(finder, Err(ParseError::IncompatibleJoin(..))));
+    }
+
+    #[test]
+    fn cast_strict() {
+        assert_eq!(
+            cast_value("abc".to_string(), CastType::Int, false),
+            Value::Int(0)
+        );
+        assert_eq!(
+            cast_value("abc".to_string(), CastType::Int, true),
+            Value::Null
+        );
+        assert_eq!(
+            cast_value("abc".to_string(), CastType::Float, false),
+            Value::Float(0.0)
+        );
+        assert_eq!(
+            cast_value("abc".to_string(), CastType::Float, true),
+            Value::Null
+        );
+        assert_eq!(
+            cast_value("0".to_string(), CastType::Int, true),
+            Value::Int(0)
+        );
+        assert_eq!(
+            cast_value("1.5".to_string(), CastType::Float, true),
+            Value::Float(1.5)
+        );
+    }
+
+    #[test]
+    fn cast_numeric_arrays() {
+        assert_eq!(
+            cast_value("1, 2 ,3,".to_string(), CastType::IntArray, false),
+            Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)])
+        );
+        assert_eq!(
+            cast_value("1, x, 3.5".to_string(), CastType::IntArray, false),
+            Value::Array(vec![Value::Int(1)])
+        );
+        assert_eq!(
+            cast_value("1.5, n/a, 2,".to_string(), CastType::FloatArray, false),
+            Value::Array(vec![Value::Float(1.5), Value::Float(2.0)])
+        );
+        assert_eq!(
+            cast_value(String::new(), CastType::FloatArray, false),
+            Value::Array(vec![])
+        );
+
+        let cfg = Config::from_yaml("{name: sizes, base_path: p, extract: text, cast: int_array}");
+        assert!(matches!(cfg.unwrap().cast, CastType::IntArray));
+    }
+
+    #[test]
+    fn split_selector_by_top_level_commas() {
+        assert_eq!(
+            split_selector(".header a, .footer a,"),
+            vec![".header a", ".footer a"]
+        );
+        assert_eq!(
+            split_selector(r#"a[title="a, b"], :is(p, div) > a"#),
+            vec![r#"a[title="a, b"]"#, ":is(p, div) > a"]
+        );
+    }
+
+    #[test]
+    fn relax_class_and_id_selectors() {
+        assert_eq!(
+            relax_class_selectors(r#"div.Result > a#Main[title=".x"]"#).unwrap(),
+            r#"div[class~="Result" i] > a[id="Main" i][title=".x"]"#
+        );
+        assert_eq!(relax_class_selectors(r"div.a\.b"), None);
+    }
 }
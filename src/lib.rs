@@ -1,13 +1,17 @@
 pub mod config;
 pub mod errors;
 pub mod finder;
+#[cfg(feature = "markdown")]
+mod markdown;
 pub mod pipeline;
 pub mod sanitize_policy;
 pub mod value;
 mod value_from;
+mod value_json;
 
-pub use self::config::{CastType, Config};
+pub use self::config::{CastType, Config, InputMode, PageField};
 pub use self::errors::*;
 pub use self::finder::Finder;
-pub use self::pipeline::{Pipeline, Proc};
+pub use self::pipeline::{CustomProcFn, Pipeline, PipelineOutput, Proc, TerminalProc};
 pub use self::value::Value;
+pub use self::value_json::SerializeOptions;
@@ -0,0 +1,307 @@
+use dom_query::{Document, NodeRef};
+
+const SKIPPED_TAGS: [&str; 6] = ["head", "script", "style", "template", "noscript", "iframe"];
+const BLOCK_TAGS: [&str; 16] = [
+    "html",
+    "body",
+    "main",
+    "article",
+    "section",
+    "header",
+    "footer",
+    "aside",
+    "nav",
+    "div",
+    "p",
+    "figure",
+    "figcaption",
+    "form",
+    "dl",
+    "address",
+];
+
+/// Converts the html into Markdown. It supports headings, paragraphs, links, images, lists, tables (as pipe tables),
+/// block quotes, code, bold (`**`) and italic (`_`) text. Other elements are replaced with their content.
+/// The conversion is best-effort: the result is meant for reading and archiving, not for a lossless round-trip.
+pub(crate) fn html_to_markdown(html: &str) -> String {
+    let doc = Document::fragment(html);
+    let mut blocks = Vec::new();
+    render_blocks(&doc.root(), &mut blocks);
+    blocks.join("\n\n")
+}
+
+/// Renders the children of the node as a sequence of blocks.
+/// Adjacent inline children are collected into a single paragraph.
+fn render_blocks(node: &NodeRef, blocks: &mut Vec<String>) {
+    let mut inline = String::new();
+    for child in node.children_it(false) {
+        let name = child.node_name().map(|n| n.to_ascii_lowercase());
+        let Some(name) = name.filter(|_| child.is_element()) else {
+            render_inline(&child, &mut inline);
+            continue;
+        };
+        if SKIPPED_TAGS.contains(&name.as_str()) {
+            continue;
+        }
+        let block = match name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                let text = inline_text(&child);
+                (!text.is_empty()).then(|| format!("{} {}", "#".repeat(level), text))
+            }
+            "ul" | "ol" => Some(render_list(&child, name == "ol")).filter(|s| !s.is_empty()),
+            "blockquote" => {
+                let mut inner = Vec::new();
+                render_blocks(&child, &mut inner);
+                let quoted: Vec<String> = inner
+                    .join("\n\n")
+                    .lines()
+                    .map(|line| format!("> {}", line).trim_end().to_string())
+                    .collect();
+                Some(quoted.join("\n")).filter(|s| !s.is_empty())
+            }
+            "pre" => {
+                let code = child.text();
+                Some(format!("```\n{}\n```", code.trim_end_matches('\n')))
+            }
+            "hr" => Some("---".to_string()),
+            "table" => Some(render_table(&child)).filter(|s| !s.is_empty()),
+            _ if BLOCK_TAGS.contains(&name.as_str()) || name == "li" => {
+                flush_paragraph(&mut inline, blocks);
+                render_blocks(&child, blocks);
+                None
+            }
+            _ => {
+                render_inline(&child, &mut inline);
+                continue;
+            }
+        };
+        flush_paragraph(&mut inline, blocks);
+        blocks.extend(block);
+    }
+    flush_paragraph(&mut inline, blocks);
+}
+
+/// Renders the list items, nested blocks of an item are indented under its marker.
+fn render_list(list: &NodeRef, ordered: bool) -> String {
+    let mut items = Vec::new();
+    let items_it = list
+        .element_children()
+        .into_iter()
+        .filter(|child| is_element_named(child, "li"));
+    for (i, item) in items_it.enumerate() {
+        let marker = if ordered {
+            format!("{}. ", i + 1)
+        } else {
+            "- ".to_string()
+        };
+        let indent = " ".repeat(marker.len());
+        let mut inner = Vec::new();
+        render_blocks(&item, &mut inner);
+        let content = inner.join("\n");
+        let mut lines = content.lines();
+        let first = lines.next().unwrap_or_default();
+        let mut rendered = format!("{}{}", marker, first).trim_end().to_string();
+        for line in lines {
+            rendered.push('\n');
+            if !line.is_empty() {
+                rendered.push_str(&indent);
+                rendered.push_str(line);
+            }
+        }
+        items.push(rendered);
+    }
+    items.join("\n")
+}
+
+/// Renders the table as a pipe table, the first row becomes the header.
+/// Rows of nested tables are not included, missing cells are left empty.
+fn render_table(table: &NodeRef) -> String {
+    let rows: Vec<Vec<String>> = table
+        .descendants_it()
+        .filter(|node| is_element_named(node, "tr"))
+        .filter(|row| {
+            row.ancestors_it(None)
+                .find(|node| is_element_named(node, "table"))
+                .map_or(false, |t| t.id == table.id)
+        })
+        .map(|row| {
+            row.element_children()
+                .iter()
+                .filter(|cell| is_element_named(cell, "td") || is_element_named(cell, "th"))
+                .map(|cell| inline_text(cell).replace('\n', " ").replace('|', "\\|"))
+                .collect()
+        })
+        .filter(|cells: &Vec<String>| !cells.is_empty())
+        .collect();
+    let Some(columns) = rows.iter().map(Vec::len).max() else {
+        return String::new();
+    };
+    let render_row = |cells: &[String]| {
+        let mut line = String::from("|");
+        for i in 0..columns {
+            match cells.get(i).filter(|cell| !cell.is_empty()) {
+                Some(cell) => line.push_str(&format!(" {} |", cell)),
+                None => line.push_str(" |"),
+            }
+        }
+        line
+    };
+    let mut lines = vec![
+        render_row(&rows[0]),
+        render_row(&vec!["---".to_string(); columns]),
+    ];
+    lines.extend(rows[1..].iter().map(|row| render_row(row)));
+    lines.join("\n")
+}
+
+fn is_element_named(node: &NodeRef, name: &str) -> bool {
+    node.is_element() && matches!(node.node_name(), Some(ref n) if n.eq_ignore_ascii_case(name))
+}
+
+/// Appends the collected inline text as a paragraph, if it is not blank.
+fn flush_paragraph(inline: &mut String, blocks: &mut Vec<String>) {
+    let paragraph = normalize_inline(inline);
+    if !paragraph.is_empty() {
+        blocks.push(paragraph);
+    }
+    inline.clear();
+}
+
+/// Returns the normalized inline Markdown of the node's content.
+fn inline_text(node: &NodeRef) -> String {
+    let mut buf = String::new();
+    for child in node.children_it(false) {
+        render_inline(&child, &mut buf);
+    }
+    normalize_inline(&buf)
+}
+
+/// Collapses whitespace in every line and removes blank lines.
+fn normalize_inline(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn render_inline(node: &NodeRef, buf: &mut String) {
+    if node.is_text() {
+        let text = node.text();
+        let mut prev_space = false;
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !prev_space {
+                    buf.push(' ');
+                }
+                prev_space = true;
+                continue;
+            }
+            prev_space = false;
+            if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+                buf.push('\\');
+            }
+            buf.push(c);
+        }
+        return;
+    }
+    if !node.is_element() {
+        return;
+    }
+    let name = node.node_name().unwrap_or_default().to_ascii_lowercase();
+    match name.as_str() {
+        _ if SKIPPED_TAGS.contains(&name.as_str()) => {}
+        "br" => buf.push('\n'),
+        "b" | "strong" => wrap_inline(node, "**", buf),
+        "i" | "em" => wrap_inline(node, "_", buf),
+        "code" => {
+            let code = node.text();
+            let code = code.trim();
+            if code.contains('`') {
+                buf.push_str(&format!("`` {} ``", code));
+            } else if !code.is_empty() {
+                buf.push_str(&format!("`{}`", code));
+            }
+        }
+        "a" => {
+            let text = inline_text(node);
+            match node.attr("href") {
+                Some(href) if !href.is_empty() => {
+                    buf.push_str(&format!("[{}]({})", text, href.trim()));
+                }
+                _ => buf.push_str(&text),
+            }
+        }
+        "img" => {
+            if let Some(src) = node.attr("src") {
+                let alt = node.attr("alt").unwrap_or_default();
+                buf.push_str(&format!("![{}]({})", alt.trim(), src.trim()));
+            }
+        }
+        _ => {
+            for child in node.children_it(false) {
+                render_inline(&child, buf);
+            }
+        }
+    }
+}
+
+/// Wraps the non-blank content of the node with the emphasis marker.
+/// Leading and trailing whitespace is moved outside of the markers, otherwise they are not recognized.
+fn wrap_inline(node: &NodeRef, marker: &str, buf: &mut String) {
+    let mut raw = String::new();
+    for child in node.children_it(false) {
+        render_inline(&child, &mut raw);
+    }
+    let text = normalize_inline(&raw);
+    if raw.starts_with(char::is_whitespace) {
+        buf.push(' ');
+    }
+    if !text.is_empty() {
+        buf.push_str(&format!("{}{}{}", marker, text, marker));
+    }
+    if raw.ends_with(char::is_whitespace) && !text.is_empty() {
+        buf.push(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_inline() {
+        let html = r#"<p>Read <a href="/docs">the <em>docs</em></a>, use <code>cargo</code>
+        and <strong>enjoy</strong>!</p><p>2 * 3 = 6</p>"#;
+        assert_eq!(
+            html_to_markdown(html),
+            "Read [the _docs_](/docs), use `cargo` and **enjoy**!\n\n2 \\* 3 = 6"
+        );
+    }
+
+    #[test]
+    fn convert_table() {
+        let html = r#"<table>
+        <thead><tr><th>Name</th><th>Price</th></tr></thead>
+        <tbody><tr><td>A|B</td><td>1</td></tr><tr><td>C</td></tr></tbody>
+        </table>"#;
+        assert_eq!(
+            html_to_markdown(html),
+            "| Name | Price |\n| --- | --- |\n| A\\|B | 1 |\n| C | |"
+        );
+    }
+
+    #[test]
+    fn convert_blocks() {
+        let html = r#"<h2>Fruits</h2>
+        <ul><li>Apple</li><li>Citrus<ol><li>Lemon</li><li>Orange</li></ol></li></ul>
+        <blockquote><p>Quote</p><p>More</p></blockquote>
+        <pre>fn main() {}
+</pre><hr><script>alert(1)</script>"#;
+        assert_eq!(
+            html_to_markdown(html),
+            "## Fruits\n\n- Apple\n- Citrus\n  1. Lemon\n  2. Orange\n\n> Quote\n>\n> More\n\n```\nfn main() {}\n```\n\n---"
+        );
+    }
+}
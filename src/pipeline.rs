@@ -1,30 +1,169 @@
 use std::borrow::Cow;
+use std::fmt;
+use std::panic::RefUnwindSafe;
+use std::sync::{Arc, RwLock};
 
+use dom_query::{Document, NodeRef};
+use hashbrown::{HashMap, HashSet};
+use once_cell::sync::Lazy;
 use regex::Regex;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
 use crate::errors::PipelineError;
 
 use super::errors::ParseError;
 use super::sanitize_policy;
+use super::value::Value;
 
 // Constants representing the names of different pipeline processing procedures
 const REGEX_PROC: &str = "regex";
 const REGEX_FIND_PROC: &str = "regex_find";
+const REGEX_GROUP_PROC: &str = "regex_group";
+const REGEX_ALL_PROC: &str = "regex_all";
+const REGEX_ALL_DEFAULT_SEP: &str = "\n";
 const REPLACE_PROC: &str = "replace";
+const REGEX_REPLACE_PROC: &str = "regex_replace";
+const PREPEND: &str = "prepend";
+const APPEND: &str = "append";
+const DEFAULT: &str = "default";
+const COALESCE: &str = "coalesce";
 const EXTRACT_JSON: &str = "extract_json";
 const TRIM_SPACE: &str = "trim_space";
 const TRIM: &str = "trim";
 const NORMALIZE_SPACES: &str = "normalize_spaces";
+const NORMALIZE_TEXT: &str = "normalize_text";
 const HTML_UNESCAPE: &str = "html_unescape";
+const HTML_ESCAPE: &str = "html_escape";
 const POLICY_HIGHLIGHT: &str = "policy_highlight";
 const POLICY_TABLE: &str = "policy_table";
 const POLICY_LIST: &str = "policy_list";
 const POLICY_COMMON: &str = "policy_common";
+const SLUGIFY: &str = "slugify";
+const READABLE_TEXT: &str = "readable_text";
+const FIRST_SENTENCE: &str = "first_sentence";
+const FIRST_WORDS: &str = "first_words";
+const BETWEEN: &str = "between";
+const SLICE: &str = "slice";
+const WHEN_MATCH: &str = "when_match";
+const RATIO: &str = "ratio";
+const SHORT_HASH: &str = "short_hash";
+const CHECK_SHAPE: &str = "check_shape";
+const CSV_CELL: &str = "csv_cell";
+const CLEAN_TOKENS: &str = "clean_tokens";
+const TITLECASE: &str = "titlecase";
+const BUCKET: &str = "bucket";
+const URL_DECODE: &str = "url_decode";
+const PARSE_BYTES: &str = "parse_bytes";
+const SUM_NUMBERS: &str = "sum_numbers";
+const MONEY: &str = "money";
+const PRINTF: &str = "printf";
+const ORDINAL: &str = "ordinal";
+const MONEY_DEFAULT_DECIMALS: usize = 2;
+const URL_DECODE_PLUS: &str = "plus";
+#[cfg(feature = "timezones")]
+const TO_UTC: &str = "to_utc";
+#[cfg(feature = "timezones")]
+const HUMANIZE_TIME: &str = "humanize_time";
+#[cfg(feature = "timezones")]
+const HUMANIZE_TIME_EPOCH: &str = "epoch";
+#[cfg(feature = "urls")]
+const CANONICAL_URL: &str = "canonical_url";
+#[cfg(feature = "urls")]
+const RESOLVE_URL: &str = "resolve_url";
+#[cfg(feature = "lang_detect")]
+const DETECT_LANG: &str = "detect_lang";
+#[cfg(feature = "hashing")]
+const HASH: &str = "hash";
+#[cfg(feature = "markdown")]
+const MARKDOWN: &str = "markdown";
+#[cfg(feature = "markdown")]
+const TO_MARKDOWN: &str = "to_markdown";
+#[cfg(feature = "urls")]
+const CANONICAL_URL_DEFAULT_PREFIXES: [&str; 3] = ["utm_", "fbclid", "gclid"];
+/// The elements which content is not a readable text, they are skipped by `readable_text`.
+const READABLE_SKIPPED_TAGS: [&str; 4] = ["script", "style", "template", "noscript"];
+/// The elements which text is separated from the adjacent text by `readable_text`.
+const READABLE_BLOCK_TAGS: [&str; 29] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+pub(crate) const SHORT_HASH_DEFAULT_LEN: usize = 8;
+/// The length of `u64::MAX` encoded in base62.
+const SHORT_HASH_MAX_LEN: usize = 11;
+const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+#[cfg(feature = "unicode_categories")]
+const KEEP_CATEGORIES: &str = "keep_categories";
+#[cfg(feature = "unicode_categories")]
+const UNICODE_CATEGORIES: [&str; 37] = [
+    "L", "Lu", "Ll", "Lt", "Lm", "Lo", "M", "Mn", "Mc", "Me", "N", "Nd", "Nl", "No", "P", "Pc",
+    "Pd", "Ps", "Pe", "Pi", "Pf", "Po", "S", "Sm", "Sc", "Sk", "So", "Z", "Zs", "Zl", "Zp", "C",
+    "Cc", "Cf", "Cs", "Co", "Cn",
+];
+// Terminal procedures, they produce a structured `Value` and must be the last in the pipeline.
+const NUMBERS_WITH_UNITS: &str = "numbers_with_units";
+const SRCSET: &str = "srcset";
+const SRCSET_MODE_ALL: &str = "all";
+const SRCSET_MODE_BEST: &str = "best";
+const PICK_LARGEST_IMAGE: &str = "pick_largest_image";
+const DECL_PARAMS: &str = "decl_params";
+const TEXT_METRICS: &str = "text_metrics";
+const SPLIT: &str = "split";
+const CONTAINS_CI: &str = "contains_ci";
+
+/// A user-defined pipeline procedure, see `Pipeline::register`.
+/// It is `RefUnwindSafe`, so `Finder` and `Pipeline` can be used inside `std::panic::catch_unwind`.
+/// A closure that captures interior mutable state can be wrapped with `std::panic::AssertUnwindSafe`.
+pub type CustomProcFn = Arc<dyn Fn(&str) -> String + Send + Sync + RefUnwindSafe>;
+
+/// The registry of user-defined pipeline procedures.
+static CUSTOM_PROCS: Lazy<RwLock<HashMap<String, CustomProcFn>>> = Lazy::new(Default::default);
+
+static NUMBER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"-?\d+(?:\.\d+)?").unwrap());
 
 /// Represents a pipeline of processing procedures.
 #[derive(Debug)]
 pub struct Pipeline<'a> {
     procs: Vec<Proc<'a>>,
+    terminal: Option<TerminalProc>,
+    raw: Vec<Vec<String>>,
+}
+
+/// Represents the result of the `Pipeline` handling.
+#[derive(Debug)]
+pub enum PipelineOutput {
+    /// a string value, it is casted according to the `cast` option.
+    Scalar(String),
+    /// a structured value produced by a `TerminalProc`, it is not casted.
+    Structured(Value),
 }
 
 impl<'a> Pipeline<'a> {
@@ -39,13 +178,51 @@ impl<'a> Pipeline<'a> {
     /// Returns a new `Result<Pipeline, ParseError>` instance. Because regex can fail to compile and user can provide an invalid procedure.
     pub fn new<'b>(raw_pipelines: &'b Vec<Vec<String>>) -> Result<Pipeline<'a>, ParseError> {
         let mut procs = vec![];
+        let mut terminal: Option<TerminalProc> = None;
+        let mut raw = vec![];
         for proc_args in raw_pipelines {
             if let Some((proc_name, args)) = proc_args.split_first() {
+                raw.push(proc_args.clone());
+                if let Some(ref terminal_proc) = terminal {
+                    return Err(
+                        PipelineError::ProcMustBeLast(terminal_proc.name().to_string()).into(),
+                    );
+                }
+                if let Some(terminal_proc) = TerminalProc::new(proc_name, args)? {
+                    terminal = Some(terminal_proc);
+                    continue;
+                }
                 let proc = Proc::new(proc_name, args)?;
                 procs.push(proc);
             }
         }
-        Ok(Pipeline { procs })
+        Ok(Pipeline {
+            procs,
+            terminal,
+            raw,
+        })
+    }
+
+    /// Registers a custom procedure under the given name, so it can be referenced in the `pipeline` option.
+    /// The procedure must be registered before the `Finder` (or the `Pipeline`) is created,
+    /// registering a procedure with the same name again replaces it for the pipelines created afterwards.
+    /// Built-in procedures take precedence over custom procedures with the same name.
+    /// Custom procedures take no arguments.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use dom_finder::Pipeline;
+    /// Pipeline::register("strip_hash", Arc::new(|v: &str| v.trim_start_matches('#').to_string()));
+    /// let pipeline = Pipeline::new(&vec![vec!["strip_hash".to_string()]]).unwrap();
+    /// assert!(matches!(
+    ///     pipeline.handle("#top".to_string()),
+    ///     dom_finder::PipelineOutput::Scalar(s) if s == "top"
+    /// ));
+    /// ```
+    pub fn register(name: &str, f: CustomProcFn) {
+        let mut procs = CUSTOM_PROCS.write().unwrap_or_else(|e| e.into_inner());
+        procs.insert(name.to_string(), f);
     }
 
     /// Handles the given value by applying all the processing procedures in the pipeline.
@@ -56,13 +233,33 @@ impl<'a> Pipeline<'a> {
     ///
     /// # Returns
     ///
-    /// Returns the processed value as a string.
-    pub fn handle(&self, value: String) -> String {
+    /// Returns the processed value as a `PipelineOutput`.
+    /// It is `PipelineOutput::Structured` only if the pipeline ends with a `TerminalProc`.
+    pub fn handle(&self, value: String) -> PipelineOutput {
+        self.handle_with_base(value, None)
+    }
+
+    /// Handles the given value like `Pipeline::handle`, but with the document's base url (`<base href>`),
+    /// that is used by the url procedures.
+    pub(crate) fn handle_with_base(&self, value: String, base_url: Option<&str>) -> PipelineOutput {
         let mut res: String = value;
         for command in self.procs.iter() {
-            res = command.handle(&res)
+            res = command.handle_with_base(&res, base_url)
         }
-        res
+        match self.terminal {
+            Some(ref terminal) => PipelineOutput::Structured(terminal.handle(&res)),
+            None => PipelineOutput::Scalar(res),
+        }
+    }
+
+    /// Returns the raw pipeline elements, the pipeline was created from. Empty elements are omitted.
+    pub(crate) fn raw(&self) -> &[Vec<String>] {
+        &self.raw
+    }
+
+    /// Returns the terminal procedure of the pipeline, if there is one.
+    pub fn terminal(&self) -> Option<&TerminalProc> {
+        self.terminal.as_ref()
     }
 }
 
@@ -77,8 +274,33 @@ pub enum Proc<'a> {
     /// it returns the first entire match of the regex in the given value (haystack).
     /// `Regex.find` is applied It requires one argument - the `Regex`.
     RegexFind(Regex),
+    /// requires two arguments - the `Regex` and the name or the number of the capture group.
+    /// It returns only this group from the first match, or an empty string if the group did not participate in the match.
+    /// The group must exist in the regex.
+    RegexGroup(Regex, Box<str>),
+    /// requires one argument - the `Regex`, and takes an optional second argument - the separator (default is `\n`).
+    /// It returns all entire matches of the regex in the given value, joined with the separator.
+    /// `Regex.find_iter` is applied under the hood.
+    RegexAll(Regex, Cow<'a, str>),
     /// requires two arguments - the old and the new string.
     Replace(Cow<'a, str>, Cow<'a, str>),
+    /// requires two arguments - the `Regex` and the replacement template.
+    /// It replaces all matches of the regex, the template supports `$1` and `$name` (or `${name}`) references
+    /// to capture groups, i.e. `[regex_replace, '(\d+)-(\d+)-(\d+)', '$3/$2/$1']`.
+    /// `Regex.replace_all` is applied under the hood.
+    RegexReplace(Regex, Box<str>),
+    /// requires one argument - the prefix. It adds the prefix to the start of the value.
+    Prepend(Box<str>),
+    /// requires one argument - the suffix. It adds the suffix to the end of the value.
+    Append(Box<str>),
+    /// requires one argument - the fallback. It returns the fallback if the value is empty or consists only of whitespace,
+    /// otherwise the value is left as is. Like other procedures it is applied only if the element is matched,
+    /// use `Config::default_value` for missing elements.
+    Default(Box<str>),
+    /// requires at least one argument - the fallbacks. It returns the value if it is not empty,
+    /// otherwise the first non-empty fallback, otherwise an empty string.
+    /// Like in `Proc::Default`, a string that consists only of whitespace is considered empty.
+    Coalesce(Vec<Box<str>>),
     /// requires one argument - the path to the json value, if the string represents a json.
     ExtractJson(Cow<'a, str>),
     /// requires no arguments. It trims spaces at the start and the end of the string.
@@ -87,8 +309,14 @@ pub enum Proc<'a> {
     Trim(Vec<char>),
     /// requires no arguments. It normalizes spaces in the string. Includes tabulations and new lines.
     NormalizeSpaces,
+    /// requires no arguments. It removes a leading byte order mark (`\u{FEFF}`)
+    /// and converts `\r\n` and `\r` line endings to `\n`. Other whitespace is left intact.
+    NormalizeText,
     /// unescape html entities, requires no arguments.
     HtmlUnescape,
+    /// escapes `&`, `<` and `>` characters for using the value as an html text, requires no arguments.
+    /// It is the inverse of `HtmlUnescape`. Quotes are not escaped, so the result is not suitable for attribute values.
+    HtmlEscape,
     /// removes all html tags from the result except `<b>`, `<em>`, and `<i>`,  requires no arguments.
     PolicyHighlight,
     /// removes all html tags from the result except  tags from  `PolicyHighlight` and
@@ -100,6 +328,157 @@ pub enum Proc<'a> {
     /// removes all html tags from the result except  tags from  `PolicyHighlight`,
     /// `PolicyTable` and `PolicyList`, requires no arguments.
     PolicyCommon,
+    /// converts the string into a slug: lowercases it, removes diacritics,
+    /// replaces every run of non-alphanumeric characters with a single `-`
+    /// and trims leading and trailing `-`. Requires no arguments.
+    Slugify,
+    /// converts an html fragment into a readable plain text in a single pass, requires no arguments.
+    /// It parses the value as an html fragment and collects its text nodes, so:
+    /// * all tags are removed, the text of block elements (i.e. `p`, `div`, `li`, `br`, `td`) is separated with a space;
+    /// * the content of `script`, `style`, `template` and `noscript` elements is skipped;
+    /// * html entities are decoded;
+    /// * every run of whitespace characters (including `&nbsp;`) is replaced with a single space,
+    ///   leading and trailing whitespace is removed.
+    ReadableText,
+    /// returns the text up to and including the first sentence terminator (`.`, `!` or `?`)
+    /// that is followed by a whitespace or by the end of the string, requires no arguments.
+    /// The detection is naive: abbreviations like `Dr. Smith` are treated as the end of a sentence.
+    FirstSentence,
+    /// requires one argument - the number of words. It returns the first N whitespace-separated words,
+    /// joined with a single space.
+    FirstWords(usize),
+    /// requires two arguments - the start and the end markers. It returns the substring between the first occurrence
+    /// of the start marker and the next occurrence of the end marker. Returns an empty string if any marker is missing.
+    Between(Cow<'a, str>, Cow<'a, str>),
+    /// requires one argument - the start index, and takes an optional argument - the end index (exclusive).
+    /// It returns the chars of the value in the given range, negative indices count from the end,
+    /// i.e. `[slice, '0', '4']` returns the first 4 chars and `[slice, '-3']` returns the last 3 chars.
+    /// Out-of-range indices are clamped to the value bounds.
+    Slice(isize, Option<isize>),
+    /// requires at least two arguments - the condition regex and the nested procedure (its name and its arguments),
+    /// i.e. `[when_match, '^http:', replace, 'http:', 'https:']`.
+    /// The nested procedure is applied only if the value matches the condition, otherwise the value is left as is.
+    WhenMatch(Regex, Box<Proc<'a>>),
+    /// requires no arguments. It splits the value formatted as `a/b` by `/` and returns the result of the division,
+    /// i.e. `3/4` becomes `0.75`. Returns an empty string if any part is not a number or if the divisor is zero.
+    Ratio,
+    /// takes an optional argument - the length of the result (from 1 to 11, default is 8).
+    /// It returns a base62 representation of the 64-bit FNV-1a hash of the value.
+    /// The result is stable across runs and platforms, but it is not a cryptographic hash.
+    ShortHash(usize),
+    /// requires one argument - a compact type spec, i.e. `object:{id:int,name:string,tags:array:[string]}`.
+    /// It returns the value unchanged if it is a json that conforms to the spec, otherwise it returns an empty string.
+    /// See `Shape` for the spec syntax.
+    CheckShape(Shape),
+    /// requires no arguments. It makes the value safe for a CSV cell according to RFC 4180:
+    /// if the value contains a comma, a double quote, `\r` or `\n`, it is wrapped in double quotes
+    /// and inner double quotes are doubled. Otherwise the value is left as is.
+    CsvCell,
+    /// requires one argument - a non-empty separator. It splits the value by the separator, trims every token,
+    /// drops empty and duplicate tokens (keeping the first occurrence) and joins the rest with the separator,
+    /// i.e. ` a ,b, a ,,c ` becomes `a,b,c` with the `,` separator.
+    CleanTokens(Box<str>),
+    /// requires no arguments. It uppercases the first alphabetic character of every whitespace-separated word
+    /// and lowercases the rest of alphabetic characters, i.e. `hELLO   world` becomes `Hello   World`.
+    /// Whitespace and non-alphabetic characters are left intact.
+    TitleCase,
+    /// requires at least one argument - a bucket formatted as `a-b:label` (an inclusive range) or `a:label` (a single number),
+    /// i.e. `[bucket, '0-2:low', '3-4:mid', '5:high']`. It parses the value as a number and returns the label
+    /// of the first bucket that contains it. Returns an empty string if the value is not a number or no bucket matches.
+    Bucket(Vec<(f64, f64, String)>),
+    /// takes an optional argument - `plus`, if it is set then `+` is decoded as a space (like in html forms).
+    /// It decodes percent-encoded sequences, i.e. `%2F` becomes `/`. It decodes only once, so `%2520` becomes `%20`.
+    /// Malformed sequences are left as is, invalid UTF-8 sequences are replaced with `U+FFFD`.
+    UrlDecode(bool),
+    /// takes an optional argument - the number of decimals (default is 2). It formats the number with exactly
+    /// the given number of decimals, rounding half away from zero, i.e. `5` becomes `5.00` and `5.125` becomes `5.13`.
+    /// Rounding is done on the decimal representation, so `1.005` becomes `1.01`. No thousands separators are added.
+    /// Returns an empty string if the value is not a number.
+    Money(usize),
+    /// requires one argument - a printf-style format spec with exactly one conversion, i.e. `%08.2f` or `$%.2f`.
+    /// See `PrintfSpec` for the supported subset. It parses the value as a number and formats it with the spec.
+    /// If the value is not a number, it is left as is.
+    Printf(PrintfSpec),
+    /// requires no arguments. It parses the value as an integer and appends the English ordinal suffix,
+    /// i.e. `1` becomes `1st`, `22` becomes `22nd` and `113` becomes `113th`.
+    /// If the value is not an integer, it is left as is.
+    Ordinal,
+    /// requires no arguments. It parses a size string like `1.5 MB` into the number of bytes, i.e. `1500000`.
+    /// Decimal units (`KB`, `MB`, `GB`, `TB`) are multiples of 1000 and binary units (`KiB`, `MiB`, `GiB`, `TiB`)
+    /// are multiples of 1024. Units are case-insensitive, `B`, `byte` and `bytes` (or no unit) mean bytes.
+    /// The result is rounded to the nearest integer. Returns an empty string if the value can not be parsed.
+    ParseBytes,
+    /// requires no arguments. It finds all numbers in the value and returns their sum as a numeric string,
+    /// i.e. `12 apples, 8 oranges` becomes `20`, so it can be combined with the `cast` option.
+    /// The sum is an integer if all numbers are integers, otherwise a float.
+    /// A `-` is taken as a sign only at the start of the value or after a whitespace, so `1-2` gives `3`.
+    /// Thousands separators are not supported. If there are no numbers, the result is `0`.
+    SumNumbers,
+    /// requires one or two arguments - the input format (`chrono` strftime syntax, i.e. `%Y-%m-%d %H:%M %:z`)
+    /// and an optional timezone to assume if the format has no offset: a fixed offset (`+02:00`)
+    /// or an IANA name (`Europe/Berlin`). It returns the instant as an RFC 3339 UTC string, i.e. `2023-06-01T10:00:00Z`.
+    /// An offset parsed from the value takes precedence over the assumed timezone.
+    /// For IANA timezones a local time that occurs twice because of a DST transition is resolved to the earliest instant,
+    /// a local time that is skipped by a DST transition gives an empty string.
+    /// If the value can not be parsed, the result is an empty string. Available only with the `timezones` feature.
+    #[cfg(feature = "timezones")]
+    ToUtc(Cow<'a, str>, Option<AssumedTz>),
+    /// requires one argument - the input format: `epoch` for unix timestamps in seconds,
+    /// or a `chrono` strftime format (like in `ToUtc`, a value without an offset is taken as UTC).
+    /// It returns the difference between the instant and "now" as a phrase, i.e. `3 days ago` or `in 2 hours`.
+    /// The difference is rounded down to the largest whole unit: seconds less than a minute give `just now`,
+    /// a month is 30 days and a year is 365 days. "Now" is the moment when the value is handled (during parsing),
+    /// so the same document gives different results over time.
+    /// If the value can not be parsed, the result is an empty string. Available only with the `timezones` feature.
+    #[cfg(feature = "timezones")]
+    HumanizeTime(Cow<'a, str>),
+    /// takes optional arguments - the prefixes of query parameters to remove (default are `utm_`, `fbclid` and `gclid`).
+    /// It normalizes an absolute url for deduplication: removes the fragment, the default port and matching query parameters,
+    /// sorts the remaining query parameters and lowercases the host. The query is re-encoded, i.e. `%20` becomes `+`.
+    /// A relative url is resolved against the document's `<base href>` first.
+    /// If the value is not an absolute url and it can not be resolved, the result is an empty string.
+    /// Available only with the `urls` feature.
+    #[cfg(feature = "urls")]
+    CanonicalUrl(Vec<String>),
+    /// requires one argument - the absolute base url or an empty string.
+    /// It resolves a relative url (i.e. `/foo` or `../bar`) against the base, which is chosen in the following order:
+    /// 1. the document's `<base href>` (if it is relative, it is resolved against the configured base url);
+    /// 2. the configured base url.
+    ///
+    /// If there is no base or the value can not be joined with it, the value is left as is.
+    /// Available only with the `urls` feature.
+    #[cfg(feature = "urls")]
+    ResolveUrl(Box<str>),
+    /// requires no arguments. It detects the language of the text with the `whatlang` n-gram detector
+    /// and returns its ISO 639-1 code, i.e. `en`. If the language can not be detected, the result is an empty string.
+    /// The detection is best-effort and may be wrong for short snippets.
+    /// Available only with the `lang_detect` feature.
+    #[cfg(feature = "lang_detect")]
+    DetectLang,
+    /// requires one argument - the hash algorithm: `sha1`, `sha256` or `md5`.
+    /// It returns the lowercase hex digest of the value. Available only with the `hashing` feature.
+    #[cfg(feature = "hashing")]
+    Hash(HashAlgo),
+    /// requires no arguments. It converts the html (i.e. extracted with `extract: html`) into Markdown.
+    /// It is registered as `markdown` and `to_markdown` (i.e. to follow the `policy_common` sanitizing).
+    /// Supported elements are:
+    /// * `h1`-`h6` - `#` headings, `p` and other block elements - paragraphs;
+    /// * `b`, `strong` - `**bold**`, `i`, `em` - `_italic_`, `code` - `` `code` ``, `pre` - fenced code blocks;
+    /// * `a` - `[text](url)`, `img` - `![alt](src)`;
+    /// * `ul`, `ol` - `- ` and `1. ` list items, nested lists are indented;
+    /// * `table` - pipe tables, the first row becomes the header;
+    /// * `blockquote` - `> ` quotes, `hr` - `---`.
+    ///
+    /// Other elements are replaced with their content. Available only with the `markdown` feature.
+    #[cfg(feature = "markdown")]
+    Markdown,
+    /// a user-defined procedure, registered with `Pipeline::register`. It takes no arguments.
+    Custom(CustomProc),
+    /// requires at least one argument - the unicode general categories to keep, i.e. `L`, `N`, `P`, `Zs`.
+    /// A one-letter argument matches the whole major category (`L` matches `Lu`, `Ll`, etc.).
+    /// All characters from other categories are removed. Available only with the `unicode_categories` feature.
+    #[cfg(feature = "unicode_categories")]
+    KeepCategories(Vec<String>),
 }
 
 impl Proc<'_> {
@@ -125,6 +504,22 @@ impl Proc<'_> {
                 validate_args_len(proc_name, args.len(), 1)?;
                 Proc::RegexFind(Regex::new(&args[0])?)
             }
+            REGEX_GROUP_PROC => {
+                validate_args_len(proc_name, args.len(), 2)?;
+                let re = Regex::new(&args[0])?;
+                let group = args[1].as_str();
+                let exists = match group.parse::<usize>() {
+                    Ok(idx) => idx < re.captures_len(),
+                    Err(_) => re.capture_names().any(|name| name == Some(group)),
+                };
+                if !exists {
+                    return Err(PipelineError::ProcInvalidArgument(
+                        proc_name.to_string(),
+                        args[1].clone(),
+                    ));
+                }
+                Proc::RegexGroup(re, group.into())
+            }
             EXTRACT_JSON => {
                 validate_args_len(proc_name, args.len(), 1)?;
                 Proc::ExtractJson(Cow::from(args[0].clone()))
@@ -133,6 +528,31 @@ impl Proc<'_> {
                 validate_args_len(proc_name, args.len(), 2)?;
                 Proc::Replace(Cow::from(args[0].clone()), Cow::from(args[1].clone()))
             }
+            REGEX_ALL_PROC => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let sep = args.get(1).map_or(REGEX_ALL_DEFAULT_SEP, |s| s.as_str());
+                Proc::RegexAll(Regex::new(&args[0])?, Cow::from(sep.to_string()))
+            }
+            REGEX_REPLACE_PROC => {
+                validate_args_len(proc_name, args.len(), 2)?;
+                Proc::RegexReplace(Regex::new(&args[0])?, args[1].as_str().into())
+            }
+            PREPEND => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                Proc::Prepend(args[0].as_str().into())
+            }
+            APPEND => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                Proc::Append(args[0].as_str().into())
+            }
+            DEFAULT => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                Proc::Default(args[0].as_str().into())
+            }
+            COALESCE => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                Proc::Coalesce(args.iter().map(|arg| arg.as_str().into()).collect())
+            }
             TRIM_SPACE => Proc::TrimSpace,
             TRIM => {
                 validate_args_len(proc_name, args.len(), 1)?;
@@ -140,26 +560,215 @@ impl Proc<'_> {
                 Proc::Trim(cut_set)
             }
             NORMALIZE_SPACES => Proc::NormalizeSpaces,
+            NORMALIZE_TEXT => Proc::NormalizeText,
             HTML_UNESCAPE => Proc::HtmlUnescape,
+            HTML_ESCAPE => Proc::HtmlEscape,
             POLICY_HIGHLIGHT => Proc::PolicyHighlight,
             POLICY_TABLE => Proc::PolicyTable,
             POLICY_LIST => Proc::PolicyList,
             POLICY_COMMON => Proc::PolicyCommon,
-            _ => return Err(PipelineError::ProcDoesNotExist(proc_name.to_string())),
+            SLUGIFY => Proc::Slugify,
+            READABLE_TEXT => Proc::ReadableText,
+            FIRST_SENTENCE => Proc::FirstSentence,
+            FIRST_WORDS => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let count = args[0].parse::<usize>().map_err(|_| {
+                    PipelineError::ProcInvalidArgument(proc_name.to_string(), args[0].clone())
+                })?;
+                Proc::FirstWords(count)
+            }
+            SLICE => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let parse_index = |arg: &String| {
+                    arg.parse::<isize>().map_err(|_| {
+                        PipelineError::ProcInvalidArgument(proc_name.to_string(), arg.clone())
+                    })
+                };
+                let end = args.get(1).map(parse_index).transpose()?;
+                Proc::Slice(parse_index(&args[0])?, end)
+            }
+            BETWEEN => {
+                validate_args_len(proc_name, args.len(), 2)?;
+                Proc::Between(Cow::from(args[0].clone()), Cow::from(args[1].clone()))
+            }
+            WHEN_MATCH => {
+                validate_args_len(proc_name, args.len(), 2)?;
+                let nested = Proc::new(&args[1], &args[2..])?;
+                Proc::WhenMatch(Regex::new(&args[0])?, Box::new(nested))
+            }
+            RATIO => Proc::Ratio,
+            SHORT_HASH => {
+                let len = match args.first() {
+                    Some(arg) => arg
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|len| (1..=SHORT_HASH_MAX_LEN).contains(len))
+                        .ok_or_else(|| {
+                            PipelineError::ProcInvalidArgument(proc_name.to_string(), arg.clone())
+                        })?,
+                    None => SHORT_HASH_DEFAULT_LEN,
+                };
+                Proc::ShortHash(len)
+            }
+            CHECK_SHAPE => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let shape = Shape::parse(&args[0]).ok_or_else(|| {
+                    PipelineError::ProcInvalidArgument(proc_name.to_string(), args[0].clone())
+                })?;
+                Proc::CheckShape(shape)
+            }
+            CSV_CELL => Proc::CsvCell,
+            CLEAN_TOKENS => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                if args[0].is_empty() {
+                    return Err(PipelineError::ProcInvalidArgument(
+                        proc_name.to_string(),
+                        args[0].clone(),
+                    ));
+                }
+                Proc::CleanTokens(args[0].as_str().into())
+            }
+            TITLECASE => Proc::TitleCase,
+            PARSE_BYTES => Proc::ParseBytes,
+            SUM_NUMBERS => Proc::SumNumbers,
+            PRINTF => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let spec = PrintfSpec::parse(&args[0]).ok_or_else(|| {
+                    PipelineError::ProcInvalidArgument(proc_name.to_string(), args[0].clone())
+                })?;
+                Proc::Printf(spec)
+            }
+            ORDINAL => Proc::Ordinal,
+            MONEY => {
+                let decimals = match args.first() {
+                    Some(arg) => arg.parse::<usize>().map_err(|_| {
+                        PipelineError::ProcInvalidArgument(proc_name.to_string(), arg.clone())
+                    })?,
+                    None => MONEY_DEFAULT_DECIMALS,
+                };
+                Proc::Money(decimals)
+            }
+            URL_DECODE => match args.first().map(|s| s.as_str()) {
+                None => Proc::UrlDecode(false),
+                Some(URL_DECODE_PLUS) => Proc::UrlDecode(true),
+                Some(arg) => {
+                    return Err(PipelineError::ProcInvalidArgument(
+                        proc_name.to_string(),
+                        arg.to_string(),
+                    ))
+                }
+            },
+            BUCKET => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let buckets = args
+                    .iter()
+                    .map(|arg| {
+                        parse_bucket(arg).ok_or_else(|| {
+                            PipelineError::ProcInvalidArgument(proc_name.to_string(), arg.clone())
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Proc::Bucket(buckets)
+            }
+            #[cfg(feature = "timezones")]
+            TO_UTC => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let tz = match args.get(1) {
+                    Some(arg) => Some(AssumedTz::parse(arg).ok_or_else(|| {
+                        PipelineError::ProcInvalidArgument(proc_name.to_string(), arg.clone())
+                    })?),
+                    None => None,
+                };
+                Proc::ToUtc(Cow::from(args[0].clone()), tz)
+            }
+            #[cfg(feature = "timezones")]
+            HUMANIZE_TIME => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                Proc::HumanizeTime(Cow::from(args[0].clone()))
+            }
+            #[cfg(feature = "urls")]
+            CANONICAL_URL => {
+                if args.is_empty() {
+                    let prefixes = CANONICAL_URL_DEFAULT_PREFIXES.iter().map(|p| p.to_string());
+                    Proc::CanonicalUrl(prefixes.collect())
+                } else {
+                    Proc::CanonicalUrl(args.to_vec())
+                }
+            }
+            #[cfg(feature = "urls")]
+            RESOLVE_URL => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                if !args[0].is_empty() {
+                    url::Url::parse(&args[0]).map_err(|_| {
+                        PipelineError::ProcInvalidArgument(proc_name.to_string(), args[0].clone())
+                    })?;
+                }
+                Proc::ResolveUrl(args[0].clone().into_boxed_str())
+            }
+            #[cfg(feature = "lang_detect")]
+            DETECT_LANG => Proc::DetectLang,
+            #[cfg(feature = "hashing")]
+            HASH => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                let algo = HashAlgo::parse(&args[0]).ok_or_else(|| {
+                    PipelineError::ProcInvalidArgument(proc_name.to_string(), args[0].clone())
+                })?;
+                Proc::Hash(algo)
+            }
+            #[cfg(feature = "markdown")]
+            MARKDOWN | TO_MARKDOWN => Proc::Markdown,
+            #[cfg(feature = "unicode_categories")]
+            KEEP_CATEGORIES => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                if let Some(cat) = args
+                    .iter()
+                    .find(|cat| !UNICODE_CATEGORIES.contains(&cat.as_str()))
+                {
+                    return Err(PipelineError::ProcInvalidArgument(
+                        proc_name.to_string(),
+                        cat.clone(),
+                    ));
+                }
+                Proc::KeepCategories(args.to_vec())
+            }
+            _ => {
+                let procs = CUSTOM_PROCS.read().unwrap_or_else(|e| e.into_inner());
+                let Some(f) = procs.get(proc_name) else {
+                    return Err(PipelineError::ProcDoesNotExist(proc_name.to_string()));
+                };
+                if let Some(arg) = args.first() {
+                    return Err(PipelineError::ProcInvalidArgument(
+                        proc_name.to_string(),
+                        arg.clone(),
+                    ));
+                }
+                Proc::Custom(CustomProc {
+                    name: proc_name.to_string(),
+                    f: f.clone(),
+                })
+            }
         };
         Ok(proc_opt)
     }
 
+    /// Handles the given value without the document's base url.
+    #[cfg(test)]
+    fn handle(&self, value: &str) -> String {
+        self.handle_with_base(value, None)
+    }
+
     /// Handles the given value by applying the processing procedure.
     ///
     /// # Arguments
     ///
     /// * `value` - The input value to be processed.
+    /// * `base_url` - The document's `<base href>`, if there is one. It is used by the url procedures.
     ///
     /// # Returns
     ///
     /// Returns the processed value as a string.
-    fn handle(&self, value: &str) -> String {
+    #[cfg_attr(not(feature = "urls"), allow(clippy::only_used_in_recursion))]
+    fn handle_with_base(&self, value: &str, base_url: Option<&str>) -> String {
         match self {
             Proc::Regex(re) => re_extract_matches(re, value),
             Proc::RegexFind(re) => re
@@ -167,16 +776,548 @@ impl Proc<'_> {
                 .map(|m| m.as_str())
                 .unwrap_or_default()
                 .to_string(),
+            Proc::RegexGroup(re, group) => re
+                .captures(value)
+                .and_then(|caps| match group.parse::<usize>() {
+                    Ok(idx) => caps.get(idx),
+                    Err(_) => caps.name(group),
+                })
+                .map(|m| m.as_str())
+                .unwrap_or_default()
+                .to_string(),
             Proc::Replace(old, new) => value.replace(old.as_ref(), new),
+            Proc::RegexAll(re, sep) => re
+                .find_iter(value)
+                .map(|m| m.as_str())
+                .collect::<Vec<&str>>()
+                .join(sep),
+            Proc::RegexReplace(re, rep) => re.replace_all(value, rep.as_ref()).into_owned(),
+            Proc::Prepend(prefix) => format!("{}{}", prefix, value),
+            Proc::Append(suffix) => format!("{}{}", value, suffix),
+            Proc::Default(fallback) if value.trim().is_empty() => fallback.to_string(),
+            Proc::Default(_) => value.to_string(),
+            Proc::Coalesce(fallbacks) => std::iter::once(value)
+                .chain(fallbacks.iter().map(|f| f.as_ref()))
+                .find(|v| !v.trim().is_empty())
+                .unwrap_or_default()
+                .to_string(),
             Proc::ExtractJson(path) => gjson::get(value, path).to_string(),
             Proc::TrimSpace => value.trim().to_string(),
             Proc::Trim(pat) => value.trim_matches(pat.as_slice()).to_string(),
             Proc::NormalizeSpaces => normalize_spaces(value),
+            Proc::NormalizeText => normalize_text(value),
             Proc::HtmlUnescape => html_escape::decode_html_entities(value).to_string(),
+            Proc::HtmlEscape => html_escape::encode_text(value).to_string(),
             Proc::PolicyHighlight => sanitize_policy::HIGHLIGHT_POLICY.clean(value),
             Proc::PolicyTable => sanitize_policy::TABLE_POLICY.clean(value),
             Proc::PolicyList => sanitize_policy::LIST_POLICY.clean(value),
             Proc::PolicyCommon => sanitize_policy::COMMON_POLICY.clean(value),
+            Proc::Slugify => slugify(value),
+            Proc::ReadableText => readable_text(value),
+            Proc::FirstSentence => first_sentence(value),
+            Proc::FirstWords(count) => value
+                .split_whitespace()
+                .take(*count)
+                .collect::<Vec<&str>>()
+                .join(" "),
+            Proc::Between(start, end) => between(value, start, end).to_string(),
+            Proc::Slice(start, end) => slice(value, *start, *end),
+            Proc::WhenMatch(re, nested) => {
+                if re.is_match(value) {
+                    nested.handle_with_base(value, base_url)
+                } else {
+                    value.to_string()
+                }
+            }
+            Proc::Ratio => ratio(value).map(|r| r.to_string()).unwrap_or_default(),
+            Proc::ShortHash(len) => short_hash(value, *len),
+            Proc::CheckShape(shape) => {
+                if gjson::valid(value) && shape.is_conformed(&gjson::parse(value)) {
+                    value.to_string()
+                } else {
+                    String::new()
+                }
+            }
+            Proc::CsvCell => csv_cell(value),
+            Proc::CleanTokens(sep) => clean_tokens(value, sep),
+            Proc::TitleCase => titlecase(value),
+            Proc::Printf(spec) => spec.format(value).unwrap_or_else(|| value.to_string()),
+            Proc::Ordinal => ordinal(value).unwrap_or_else(|| value.to_string()),
+            Proc::ParseBytes => parse_bytes(value)
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            Proc::SumNumbers => sum_numbers(value),
+            Proc::Money(decimals) => money(value, *decimals).unwrap_or_default(),
+            Proc::UrlDecode(plus_as_space) => url_decode(value, *plus_as_space),
+            Proc::Bucket(buckets) => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|num| {
+                    buckets
+                        .iter()
+                        .find(|(start, end, _)| *start <= num && num <= *end)
+                })
+                .map(|(_, _, label)| label.clone())
+                .unwrap_or_default(),
+            #[cfg(feature = "timezones")]
+            Proc::ToUtc(format, tz) => to_utc(value, format, tz.as_ref()).unwrap_or_default(),
+            #[cfg(feature = "timezones")]
+            Proc::HumanizeTime(format) => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs() as i64);
+                humanize_time(value, format, now).unwrap_or_default()
+            }
+            #[cfg(feature = "urls")]
+            Proc::CanonicalUrl(prefixes) => {
+                canonical_url(value, prefixes, base_url).unwrap_or_default()
+            }
+            #[cfg(feature = "urls")]
+            Proc::ResolveUrl(base) => {
+                resolve_url(value, base, base_url).unwrap_or_else(|| value.to_string())
+            }
+            #[cfg(feature = "lang_detect")]
+            Proc::DetectLang => detect_lang(value).unwrap_or_default().to_string(),
+            #[cfg(feature = "hashing")]
+            Proc::Hash(algo) => algo.hex_digest(value),
+            #[cfg(feature = "markdown")]
+            Proc::Markdown => crate::markdown::html_to_markdown(value),
+            Proc::Custom(custom) => (custom.f)(value),
+            #[cfg(feature = "unicode_categories")]
+            Proc::KeepCategories(categories) => keep_categories(value, categories),
+        }
+    }
+}
+
+/// Represents the format spec of the `Proc::Printf` procedure: `%[flags][width][.precision]type`.
+/// * flags: `0` - pad with zeros (after the sign), `-` - align to the left, `+` - always print the sign;
+/// * width: the minimum width of the formatted number;
+/// * precision: the number of decimals for `f` (default is 6), it is ignored for `d`;
+/// * type: `f` - a float, `d` - an integer (the fraction of the value is truncated).
+///
+/// Text around the conversion is kept as is, `%%` is a literal `%`.
+#[derive(Debug)]
+pub struct PrintfSpec {
+    prefix: String,
+    suffix: String,
+    zero_pad: bool,
+    left_align: bool,
+    plus_sign: bool,
+    width: usize,
+    precision: Option<usize>,
+    is_float: bool,
+}
+
+impl PrintfSpec {
+    /// Parses the spec, returns `None` if the spec is invalid or it has not exactly one conversion.
+    fn parse(spec: &str) -> Option<PrintfSpec> {
+        let mut prefix = String::new();
+        let mut chars = spec.chars().peekable();
+        loop {
+            match chars.next()? {
+                '%' if chars.peek() == Some(&'%') => {
+                    chars.next();
+                    prefix.push('%');
+                }
+                '%' => break,
+                c => prefix.push(c),
+            }
+        }
+        let (mut zero_pad, mut left_align, mut plus_sign) = (false, false, false);
+        while let Some(c) = chars.peek() {
+            match c {
+                '0' => zero_pad = true,
+                '-' => left_align = true,
+                '+' => plus_sign = true,
+                _ => break,
+            }
+            chars.next();
+        }
+        let mut width = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+            width.push(c);
+        }
+        let mut precision = None;
+        if chars.next_if_eq(&'.').is_some() {
+            let mut digits = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(c);
+            }
+            precision = Some(digits.parse().ok()?);
+        }
+        let is_float = match chars.next()? {
+            'f' => true,
+            'd' => false,
+            _ => return None,
+        };
+        let mut suffix = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '%' if chars.next_if_eq(&'%').is_some() => suffix.push('%'),
+                '%' => return None,
+                c => suffix.push(c),
+            }
+        }
+        Some(PrintfSpec {
+            prefix,
+            suffix,
+            zero_pad,
+            left_align,
+            plus_sign,
+            width: if width.is_empty() {
+                0
+            } else {
+                width.parse().ok()?
+            },
+            precision,
+            is_float,
+        })
+    }
+
+    /// Formats the number according to the spec, returns `None` if the value is not a number.
+    fn format(&self, value: &str) -> Option<String> {
+        let num: f64 = value.trim().parse().ok().filter(|n: &f64| n.is_finite())?;
+        let formatted = if self.is_float {
+            format!("{:.*}", self.precision.unwrap_or(6), num)
+        } else {
+            format!("{}", num.trunc() as i64)
+        };
+        let (sign, digits) = match formatted.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None if self.plus_sign => ("+", formatted.as_str()),
+            None => ("", formatted.as_str()),
+        };
+        let len = sign.len() + digits.len();
+        let pad = self.width.saturating_sub(len);
+        let body = if self.left_align {
+            format!("{}{}{}", sign, digits, " ".repeat(pad))
+        } else if self.zero_pad {
+            format!("{}{}{}", sign, "0".repeat(pad), digits)
+        } else {
+            format!("{}{}{}", " ".repeat(pad), sign, digits)
+        };
+        Some(format!("{}{}{}", self.prefix, body, self.suffix))
+    }
+}
+
+/// Represents the expected type of a json value for the `Proc::CheckShape` procedure.
+/// The spec consists of the following types:
+/// * `any`, `null`, `bool`, `int`, `float` (any number), `string`;
+/// * `array` - any array, or `array:[T]` - an array with all elements of type `T`;
+/// * `object` - any object, or `object:{key:T,...}` - an object with the listed keys of given types.
+///   Listed keys are required, other keys are allowed.
+#[derive(Debug)]
+pub enum Shape {
+    Any,
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+    Array(Option<Box<Shape>>),
+    Object(Vec<(String, Shape)>),
+}
+
+impl Shape {
+    /// Parses the spec, returns `None` if the spec is invalid.
+    fn parse(spec: &str) -> Option<Shape> {
+        let (shape, rest) = Shape::parse_prefix(spec)?;
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        Some(shape)
+    }
+
+    /// Parses the shape at the start of the spec and returns it with the rest of the spec.
+    fn parse_prefix(spec: &str) -> Option<(Shape, &str)> {
+        let spec = spec.trim_start();
+        let name_end = spec
+            .find(|c: char| matches!(c, ':' | ',' | ']' | '}') || c.is_whitespace())
+            .unwrap_or(spec.len());
+        let (name, rest) = spec.split_at(name_end);
+        let shape = match name {
+            "any" => Shape::Any,
+            "null" => Shape::Null,
+            "bool" => Shape::Bool,
+            "int" => Shape::Int,
+            "float" => Shape::Float,
+            "string" => Shape::String,
+            "array" => {
+                let Some(rest) = rest.strip_prefix(":[") else {
+                    return Some((Shape::Array(None), rest));
+                };
+                let (item, rest) = Shape::parse_prefix(rest)?;
+                let rest = rest.trim_start().strip_prefix(']')?;
+                return Some((Shape::Array(Some(Box::new(item))), rest));
+            }
+            "object" => {
+                let Some(mut rest) = rest.strip_prefix(":{") else {
+                    return Some((Shape::Object(vec![]), rest));
+                };
+                let mut fields = vec![];
+                loop {
+                    let (key, tail) = rest.split_once(':')?;
+                    let key = key.trim();
+                    if key.is_empty() {
+                        return None;
+                    }
+                    let (field, tail) = Shape::parse_prefix(tail)?;
+                    fields.push((key.to_string(), field));
+                    let tail = tail.trim_start();
+                    if let Some(tail) = tail.strip_prefix(',') {
+                        rest = tail;
+                    } else {
+                        rest = tail.strip_prefix('}')?;
+                        break;
+                    }
+                }
+                return Some((Shape::Object(fields), rest));
+            }
+            _ => return None,
+        };
+        Some((shape, rest))
+    }
+
+    /// Returns `true` if the json value conforms to the shape.
+    fn is_conformed(&self, value: &gjson::Value) -> bool {
+        let kind = value.kind();
+        match self {
+            Shape::Any => true,
+            Shape::Null => matches!(kind, gjson::Kind::Null),
+            Shape::Bool => matches!(kind, gjson::Kind::True | gjson::Kind::False),
+            Shape::Int => {
+                matches!(kind, gjson::Kind::Number) && !value.json().contains(['.', 'e', 'E'])
+            }
+            Shape::Float => matches!(kind, gjson::Kind::Number),
+            Shape::String => matches!(kind, gjson::Kind::String),
+            Shape::Array(item) => {
+                matches!(kind, gjson::Kind::Array)
+                    && item.as_ref().map_or(true, |item| {
+                        value.array().iter().all(|v| item.is_conformed(v))
+                    })
+            }
+            Shape::Object(fields) => {
+                if !matches!(kind, gjson::Kind::Object) {
+                    return false;
+                }
+                fields.iter().all(|(key, field)| {
+                    let mut conformed = false;
+                    value.each(|k, v| {
+                        if k.str() != key {
+                            return true;
+                        }
+                        conformed = field.is_conformed(&v);
+                        false
+                    });
+                    conformed
+                })
+            }
+        }
+    }
+}
+
+/// Represents a user-defined procedure of the `Proc::Custom` variant.
+#[derive(Clone)]
+pub struct CustomProc {
+    name: String,
+    f: CustomProcFn,
+}
+
+impl fmt::Debug for CustomProc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomProc")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Represents the hash algorithm of the `Proc::Hash` procedure.
+#[cfg(feature = "hashing")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgo {
+    Sha1,
+    Sha256,
+    Md5,
+}
+
+#[cfg(feature = "hashing")]
+impl HashAlgo {
+    fn parse(name: &str) -> Option<HashAlgo> {
+        match name {
+            "sha1" => Some(HashAlgo::Sha1),
+            "sha256" => Some(HashAlgo::Sha256),
+            "md5" => Some(HashAlgo::Md5),
+            _ => None,
+        }
+    }
+
+    /// Returns the lowercase hex digest of the text.
+    fn hex_digest(&self, text: &str) -> String {
+        use sha2::Digest;
+        let digest: Vec<u8> = match self {
+            HashAlgo::Sha1 => sha1::Sha1::digest(text).to_vec(),
+            HashAlgo::Sha256 => sha2::Sha256::digest(text).to_vec(),
+            HashAlgo::Md5 => md5::Md5::digest(text).to_vec(),
+        };
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Represents the timezone that is assumed by the `Proc::ToUtc` procedure.
+#[cfg(feature = "timezones")]
+#[derive(Debug)]
+pub enum AssumedTz {
+    Fixed(chrono::FixedOffset),
+    Named(chrono_tz::Tz),
+}
+
+#[cfg(feature = "timezones")]
+impl AssumedTz {
+    fn parse(tz: &str) -> Option<AssumedTz> {
+        if let Ok(offset) = tz.parse::<chrono::FixedOffset>() {
+            return Some(AssumedTz::Fixed(offset));
+        }
+        tz.parse::<chrono_tz::Tz>().ok().map(AssumedTz::Named)
+    }
+}
+
+/// Represents a terminal procedure in the pipeline.
+/// Terminal procedures produce a structured `Value` instead of a string,
+/// so they can only be the last procedure in the pipeline.
+#[derive(Debug)]
+pub enum TerminalProc {
+    /// requires no arguments. It splits the string by whitespace and returns an array of floats,
+    /// trailing units (non-digit characters) are stripped from every token, i.e. `10.3mg 2g` becomes `[10.3, 2.0]`.
+    /// Tokens that can not be parsed as a number are skipped.
+    NumbersWithUnits,
+    /// parses the value of `srcset` attribute. It takes an optional argument - the mode:
+    /// * `all` (default) - returns an array of objects with `url` and `descriptor` keys;
+    /// * `best` - returns the url of the candidate with the highest resolution (the biggest `w` or `x` descriptor).
+    ///   A candidate without a descriptor is treated as `1x`.
+    ///
+    /// It is also registered as `pick_largest_image` (requires no arguments), which returns the url of the candidate
+    /// with the greatest width (`w` descriptor), candidates without a width are ignored.
+    Srcset(SrcsetMode),
+    /// requires no arguments. It parses CSS-style declarations (`key: value; key: value`) into an object,
+    /// i.e. the value of the `style` attribute. Every declaration is split by the first `:`, keys and values are trimmed.
+    /// Declarations without `:` or with an empty key are skipped, if a key is repeated, the last value wins.
+    DeclParams,
+    /// requires no arguments. It returns an object with the following integer fields:
+    /// * `chars` - the number of unicode characters (not bytes), including whitespace;
+    /// * `words` - the number of whitespace-separated tokens;
+    /// * `sentences` - the number of sentences. Like in `Proc::FirstSentence`, a sentence ends with a run of `.`, `!` or `?`
+    ///   followed by a whitespace or by the end of the string. Trailing text without a terminator is counted as a sentence.
+    TextMetrics,
+    /// requires one argument - a non-empty separator. It splits the value by the separator and returns an array of strings,
+    /// i.e. `a,b,c` becomes `["a", "b", "c"]`. Parts are not trimmed. An empty value gives an empty array.
+    Split(Box<str>),
+    /// requires one argument - the needle. It returns `true` if the value contains the needle ignoring case,
+    /// otherwise `false`. The needle is stored lowercased.
+    ContainsCi(Box<str>),
+}
+
+/// Represents the mode of the `TerminalProc::Srcset` procedure.
+#[derive(Debug, Clone, Copy)]
+pub enum SrcsetMode {
+    All,
+    Best,
+    Widest,
+}
+
+impl TerminalProc {
+    /// Creates a new `TerminalProc` instance if the `proc_name` is a name of a terminal procedure.
+    /// Otherwise it returns `Ok(None)`.
+    fn new(proc_name: &str, args: &[String]) -> Result<Option<Self>, PipelineError> {
+        let proc_opt = match proc_name {
+            NUMBERS_WITH_UNITS => TerminalProc::NumbersWithUnits,
+            SRCSET => {
+                let mode = match args.first().map(|s| s.as_str()) {
+                    None | Some(SRCSET_MODE_ALL) => SrcsetMode::All,
+                    Some(SRCSET_MODE_BEST) => SrcsetMode::Best,
+                    Some(mode) => {
+                        return Err(PipelineError::ProcInvalidArgument(
+                            proc_name.to_string(),
+                            mode.to_string(),
+                        ))
+                    }
+                };
+                TerminalProc::Srcset(mode)
+            }
+            PICK_LARGEST_IMAGE => TerminalProc::Srcset(SrcsetMode::Widest),
+            DECL_PARAMS => TerminalProc::DeclParams,
+            TEXT_METRICS => TerminalProc::TextMetrics,
+            SPLIT => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                if args[0].is_empty() {
+                    return Err(PipelineError::ProcInvalidArgument(
+                        proc_name.to_string(),
+                        args[0].clone(),
+                    ));
+                }
+                TerminalProc::Split(args[0].as_str().into())
+            }
+            CONTAINS_CI => {
+                validate_args_len(proc_name, args.len(), 1)?;
+                TerminalProc::ContainsCi(args[0].to_lowercase().into())
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(proc_opt))
+    }
+
+    /// Returns the name of the procedure, as it is used in the config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TerminalProc::NumbersWithUnits => NUMBERS_WITH_UNITS,
+            TerminalProc::Srcset(SrcsetMode::Widest) => PICK_LARGEST_IMAGE,
+            TerminalProc::Srcset(_) => SRCSET,
+            TerminalProc::DeclParams => DECL_PARAMS,
+            TerminalProc::TextMetrics => TEXT_METRICS,
+            TerminalProc::Split(_) => SPLIT,
+            TerminalProc::ContainsCi(_) => CONTAINS_CI,
+        }
+    }
+
+    /// Handles the given value by applying the terminal procedure.
+    fn handle(&self, value: &str) -> Value {
+        match self {
+            TerminalProc::NumbersWithUnits => numbers_with_units(value),
+            TerminalProc::Srcset(SrcsetMode::All) => parse_srcset(value)
+                .into_iter()
+                .map(|(url, descriptor)| {
+                    Value::from_iter([
+                        ("url".to_string(), Value::from(url)),
+                        ("descriptor".to_string(), Value::from(descriptor)),
+                    ])
+                })
+                .collect(),
+            TerminalProc::Srcset(SrcsetMode::Best) => parse_srcset(value)
+                .into_iter()
+                .map(|(url, descriptor)| (url, srcset_descriptor_value(descriptor)))
+                .fold(None, |best: Option<(&str, f64)>, (url, res)| match best {
+                    Some((_, best_res)) if best_res >= res => best,
+                    _ => Some((url, res)),
+                })
+                .map_or(Value::Null, |(url, _)| Value::from(url)),
+            TerminalProc::Srcset(SrcsetMode::Widest) => parse_srcset(value)
+                .into_iter()
+                .filter_map(|(url, descriptor)| {
+                    let width = descriptor.strip_suffix('w')?.parse::<u64>().ok()?;
+                    Some((url, width))
+                })
+                .fold(None, |best: Option<(&str, u64)>, (url, width)| match best {
+                    Some((_, best_width)) if best_width >= width => best,
+                    _ => Some((url, width)),
+                })
+                .map_or(Value::Null, |(url, _)| Value::from(url)),
+            TerminalProc::DeclParams => decl_params(value),
+            TerminalProc::TextMetrics => text_metrics(value),
+            TerminalProc::Split(_) if value.is_empty() => Value::Array(vec![]),
+            TerminalProc::Split(sep) => value.split(sep.as_ref()).collect(),
+            TerminalProc::ContainsCi(needle) => {
+                Value::Bool(value.to_lowercase().contains(needle.as_ref()))
+            }
         }
     }
 }
@@ -207,6 +1348,626 @@ fn normalize_spaces(text: &str) -> String {
     text.split_whitespace().collect::<Vec<&str>>().join(" ")
 }
 
+fn normalize_text(text: &str) -> String {
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Removes diacritical marks from the text, i.e. `Crème` becomes `Creme`.
+fn deaccent(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in deaccent(text).chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+fn readable_text(html: &str) -> String {
+    let fragment = Document::fragment(html);
+    let mut text = String::with_capacity(html.len());
+    let mut pending_space = false;
+    collect_readable_text(&fragment.root(), &mut text, &mut pending_space);
+    text
+}
+
+/// Appends the text of the node's descendants to `text`, collapsing whitespace.
+/// The space is pending until the next non-whitespace character, so the text is never padded.
+fn collect_readable_text(node: &NodeRef, text: &mut String, pending_space: &mut bool) {
+    for child in node.children_it(false) {
+        if child.is_text() {
+            for c in child.text().chars() {
+                if c.is_whitespace() {
+                    *pending_space = !text.is_empty();
+                } else {
+                    if *pending_space {
+                        text.push(' ');
+                        *pending_space = false;
+                    }
+                    text.push(c);
+                }
+            }
+            continue;
+        }
+        if !child.is_element() {
+            continue;
+        }
+        let name = child.node_name().unwrap_or_default().to_ascii_lowercase();
+        if READABLE_SKIPPED_TAGS.contains(&name.as_str()) {
+            continue;
+        }
+        let is_block = READABLE_BLOCK_TAGS.contains(&name.as_str());
+        if is_block && !text.is_empty() {
+            *pending_space = true;
+        }
+        collect_readable_text(&child, text, pending_space);
+        if is_block && !text.is_empty() {
+            *pending_space = true;
+        }
+    }
+}
+
+fn first_sentence(text: &str) -> String {
+    let text = text.trim_start();
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        match chars.peek() {
+            Some((_, next)) if !next.is_whitespace() => continue,
+            _ => return text[..i + c.len_utf8()].to_string(),
+        }
+    }
+    text.trim_end().to_string()
+}
+
+fn slice(text: &str, start: isize, end: Option<isize>) -> String {
+    let len = text.chars().count();
+    let clamp = |idx: isize| {
+        if idx < 0 {
+            len.saturating_sub(idx.unsigned_abs())
+        } else {
+            (idx as usize).min(len)
+        }
+    };
+    let start = clamp(start);
+    let end = end.map_or(len, clamp);
+    if start >= end {
+        return String::new();
+    }
+    text.chars().skip(start).take(end - start).collect()
+}
+
+fn between<'b>(text: &'b str, start: &str, end: &str) -> &'b str {
+    text.split_once(start)
+        .and_then(|(_, rest)| rest.split_once(end))
+        .map(|(inner, _)| inner)
+        .unwrap_or_default()
+}
+
+fn ratio(text: &str) -> Option<f64> {
+    let (a, b) = text.split_once('/')?;
+    let a: f64 = a.trim().parse().ok()?;
+    let b: f64 = b.trim().parse().ok()?;
+    if b == 0.0 {
+        return None;
+    }
+    Some(a / b)
+}
+
+#[cfg(feature = "unicode_categories")]
+fn keep_categories(text: &str, categories: &[String]) -> String {
+    text.chars()
+        .filter(|c| {
+            let abbr = unicode_general_category::get_general_category(*c).abbreviation();
+            categories
+                .iter()
+                .any(|cat| cat == abbr || (cat.len() == 1 && abbr.starts_with(cat.as_str())))
+        })
+        .collect()
+}
+
+#[cfg(feature = "timezones")]
+fn to_utc(text: &str, format: &str, tz: Option<&AssumedTz>) -> Option<String> {
+    let instant = parse_instant(text, format, tz)?;
+    Some(instant.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+}
+
+/// Parses the date and time with the strftime format, the `tz` is assumed if the value has no offset.
+#[cfg(feature = "timezones")]
+fn parse_instant(
+    text: &str,
+    format: &str,
+    tz: Option<&AssumedTz>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+    let text = text.trim();
+    let instant = match chrono::DateTime::parse_from_str(text, format) {
+        Ok(dt) => dt.with_timezone(&Utc),
+        Err(_) => {
+            let naive = NaiveDateTime::parse_from_str(text, format)
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(text, format)
+                        .ok()
+                        .and_then(|d| d.and_hms_opt(0, 0, 0))
+                })?;
+            match tz? {
+                AssumedTz::Fixed(offset) => offset
+                    .from_local_datetime(&naive)
+                    .earliest()?
+                    .with_timezone(&Utc),
+                AssumedTz::Named(tz) => tz
+                    .from_local_datetime(&naive)
+                    .earliest()?
+                    .with_timezone(&Utc),
+            }
+        }
+    };
+    Some(instant)
+}
+
+/// Describes the difference between the instant and `now` (both are unix timestamps in seconds) as a phrase.
+#[cfg(feature = "timezones")]
+fn humanize_time(text: &str, format: &str, now: i64) -> Option<String> {
+    const UNITS: [(&str, i64); 6] = [
+        ("year", 365 * 86400),
+        ("month", 30 * 86400),
+        ("week", 7 * 86400),
+        ("day", 86400),
+        ("hour", 3600),
+        ("minute", 60),
+    ];
+    let timestamp = if format == HUMANIZE_TIME_EPOCH {
+        text.trim().parse::<i64>().ok()?
+    } else {
+        let utc = AssumedTz::Fixed(chrono::FixedOffset::east_opt(0)?);
+        parse_instant(text, format, Some(&utc))?.timestamp()
+    };
+    let diff = now - timestamp;
+    let Some((unit, secs)) = UNITS.iter().find(|(_, secs)| diff.abs() >= *secs) else {
+        return Some("just now".to_string());
+    };
+    let count = diff.abs() / secs;
+    let plural = if count == 1 { "" } else { "s" };
+    if diff > 0 {
+        Some(format!("{} {}{} ago", count, unit, plural))
+    } else {
+        Some(format!("in {} {}{}", count, unit, plural))
+    }
+}
+
+#[cfg(feature = "urls")]
+fn canonical_url(text: &str, prefixes: &[String], doc_base: Option<&str>) -> Option<String> {
+    let text = text.trim();
+    let mut url = url::Url::parse(text)
+        .ok()
+        .or_else(|| url::Url::parse(doc_base?.trim()).ok()?.join(text).ok())?;
+    url.set_fragment(None);
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| !prefixes.iter().any(|p| key.starts_with(p.as_str())))
+        .map(|(key, val)| (key.into_owned(), val.into_owned()))
+        .collect();
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        pairs.sort();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    Some(url.into())
+}
+
+#[cfg(feature = "urls")]
+fn resolve_url(text: &str, base: &str, doc_base: Option<&str>) -> Option<String> {
+    let base = if base.is_empty() {
+        None
+    } else {
+        url::Url::parse(base).ok()
+    };
+    let doc_base = doc_base.and_then(|href| match base {
+        Some(ref base) => base.join(href.trim()).ok(),
+        None => url::Url::parse(href.trim()).ok(),
+    });
+    let base = doc_base.or(base)?;
+    base.join(text.trim()).ok().map(String::from)
+}
+
+#[cfg(feature = "lang_detect")]
+fn detect_lang(text: &str) -> Option<&'static str> {
+    // whatlang uses ISO 639-3 codes
+    let code = match whatlang::detect_lang(text)?.code() {
+        "afr" => "af",
+        "aka" => "ak",
+        "amh" => "am",
+        "ara" => "ar",
+        "aze" => "az",
+        "bel" => "be",
+        "ben" => "bn",
+        "bul" => "bg",
+        "cat" => "ca",
+        "ces" => "cs",
+        "cmn" => "zh",
+        "cym" => "cy",
+        "dan" => "da",
+        "deu" => "de",
+        "ell" => "el",
+        "eng" => "en",
+        "epo" => "eo",
+        "est" => "et",
+        "fin" => "fi",
+        "fra" => "fr",
+        "guj" => "gu",
+        "heb" => "he",
+        "hin" => "hi",
+        "hrv" => "hr",
+        "hun" => "hu",
+        "hye" => "hy",
+        "ind" => "id",
+        "ita" => "it",
+        "jav" => "jv",
+        "jpn" => "ja",
+        "kan" => "kn",
+        "kat" => "ka",
+        "khm" => "km",
+        "kor" => "ko",
+        "lat" => "la",
+        "lav" => "lv",
+        "lit" => "lt",
+        "mal" => "ml",
+        "mar" => "mr",
+        "mkd" => "mk",
+        "mya" => "my",
+        "nep" => "ne",
+        "nld" => "nl",
+        "nob" => "nb",
+        "ori" => "or",
+        "pan" => "pa",
+        "pes" => "fa",
+        "pol" => "pl",
+        "por" => "pt",
+        "ron" => "ro",
+        "rus" => "ru",
+        "sin" => "si",
+        "slk" => "sk",
+        "slv" => "sl",
+        "sna" => "sn",
+        "spa" => "es",
+        "srp" => "sr",
+        "swe" => "sv",
+        "tam" => "ta",
+        "tel" => "te",
+        "tgl" => "tl",
+        "tha" => "th",
+        "tuk" => "tk",
+        "tur" => "tr",
+        "ukr" => "uk",
+        "urd" => "ur",
+        "uzb" => "uz",
+        "vie" => "vi",
+        "yid" => "yi",
+        "zul" => "zu",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Parses the bucket argument formatted as `a-b:label` or `a:label` into `(start, end, label)`.
+fn parse_bucket(arg: &str) -> Option<(f64, f64, String)> {
+    let (range, label) = arg.split_once(':')?;
+    let range = range.trim();
+    // the search starts from the second char, so the start of the range may be negative
+    let (start, end) = match range.get(1..).and_then(|r| r.find('-')) {
+        Some(pos) => (&range[..pos + 1], &range[pos + 2..]),
+        None => (range, range),
+    };
+    let start: f64 = start.trim().parse().ok()?;
+    let end: f64 = end.trim().parse().ok()?;
+    if start > end {
+        return None;
+    }
+    Some((start, end, label.to_string()))
+}
+
+fn ordinal(text: &str) -> Option<String> {
+    let n: i64 = text.trim().parse().ok()?;
+    let suffix = match (n.unsigned_abs() % 10, n.unsigned_abs() % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    Some(format!("{}{}", n, suffix))
+}
+
+fn parse_bytes(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let unit_start = text.find(|c: char| c.is_alphabetic()).unwrap_or(text.len());
+    let (num, unit) = text.split_at(unit_start);
+    let num: f64 = num
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite() && *n >= 0.0)?;
+    let multiplier: f64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" | "byte" | "bytes" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024f64.powi(2),
+        "gib" => 1024f64.powi(3),
+        "tib" => 1024f64.powi(4),
+        _ => return None,
+    };
+    Some((num * multiplier).round() as u64)
+}
+
+fn money(text: &str, decimals: usize) -> Option<String> {
+    let text = text.trim();
+    let num = text.parse::<f64>().ok().filter(|num| num.is_finite())?;
+    if text.contains(['e', 'E']) {
+        return Some(format!("{:.*}", decimals, num));
+    }
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let mut digits: Vec<u8> = int_part.bytes().collect();
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    let mut int_len = digits.len();
+    digits.extend(
+        frac_part
+            .bytes()
+            .chain(std::iter::repeat(b'0'))
+            .take(decimals),
+    );
+    if matches!(frac_part.as_bytes().get(decimals), Some(b'5'..=b'9')) {
+        // round half away from zero, propagating the carry
+        let mut carry = true;
+        for d in digits.iter_mut().rev() {
+            if *d == b'9' {
+                *d = b'0';
+            } else {
+                *d += 1;
+                carry = false;
+                break;
+            }
+        }
+        if carry {
+            digits.insert(0, b'1');
+            int_len += 1;
+        }
+    }
+    let (int_digits, frac_digits) = digits.split_at(int_len);
+    let int_digits = std::str::from_utf8(int_digits)
+        .ok()?
+        .trim_start_matches('0');
+    let int_digits = if int_digits.is_empty() {
+        "0"
+    } else {
+        int_digits
+    };
+    let frac_digits = std::str::from_utf8(frac_digits).ok()?;
+    let is_zero = int_digits == "0" && frac_digits.bytes().all(|d| d == b'0');
+    let sign = if is_zero { "" } else { sign };
+    if decimals == 0 {
+        Some(format!("{}{}", sign, int_digits))
+    } else {
+        Some(format!("{}{}.{}", sign, int_digits, frac_digits))
+    }
+}
+
+fn url_decode(text: &str, plus_as_space: bool) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(b) = hex {
+                    decoded.push(b);
+                    i += 3;
+                    continue;
+                }
+                decoded.push(b'%');
+            }
+            b'+' if plus_as_space => decoded.push(b' '),
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn titlecase(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut is_word_start = true;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            is_word_start = true;
+            res.push(c);
+        } else if !c.is_alphabetic() {
+            res.push(c);
+        } else if is_word_start {
+            is_word_start = false;
+            res.extend(c.to_uppercase());
+        } else {
+            res.extend(c.to_lowercase());
+        }
+    }
+    res
+}
+
+fn csv_cell(text: &str) -> String {
+    if !text.contains([',', '"', '\r', '\n']) {
+        return text.to_string();
+    }
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+fn clean_tokens(text: &str, sep: &str) -> String {
+    let mut seen: HashSet<&str> = HashSet::new();
+    text.split(sep)
+        .map(str::trim)
+        .filter(|token| !token.is_empty() && seen.insert(token))
+        .collect::<Vec<&str>>()
+        .join(sep)
+}
+
+fn fnv1a_64(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    data.iter().fold(OFFSET_BASIS, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(PRIME)
+    })
+}
+
+pub(crate) fn short_hash(text: &str, len: usize) -> String {
+    let mut hash = fnv1a_64(text.as_bytes());
+    let mut digits = [b'0'; SHORT_HASH_MAX_LEN];
+    for digit in digits.iter_mut().rev() {
+        *digit = BASE62_ALPHABET[(hash % 62) as usize];
+        hash /= 62;
+    }
+    // the lowest digits are taken, because the highest digit of u64 is always in range `0..=2`
+    digits[SHORT_HASH_MAX_LEN - len..]
+        .iter()
+        .map(|b| *b as char)
+        .collect()
+}
+
+fn numbers_with_units(text: &str) -> Value {
+    text.split_whitespace()
+        .filter_map(|token| {
+            token
+                .trim_end_matches(|c: char| !c.is_ascii_digit())
+                .parse::<f64>()
+                .ok()
+        })
+        .collect()
+}
+
+fn sum_numbers(text: &str) -> String {
+    let mut int_sum: Option<i64> = Some(0);
+    let mut float_sum = 0.0;
+    for m in NUMBER_RE.find_iter(text) {
+        let mut token = m.as_str();
+        let is_signed = text[..m.start()]
+            .chars()
+            .next_back()
+            .map_or(true, char::is_whitespace);
+        if !is_signed {
+            token = token.trim_start_matches('-');
+        }
+        float_sum += token.parse::<f64>().unwrap_or_default();
+        int_sum = match (int_sum, token.parse::<i64>()) {
+            (Some(sum), Ok(num)) => sum.checked_add(num),
+            _ => None,
+        };
+    }
+    match int_sum {
+        Some(sum) => sum.to_string(),
+        None => float_sum.to_string(),
+    }
+}
+
+fn decl_params(text: &str) -> Value {
+    text.split(';')
+        .filter_map(|decl| decl.split_once(':'))
+        .map(|(key, val)| (key.trim(), val.trim()))
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, val)| (key.to_string(), Value::from(val)))
+        .collect()
+}
+
+fn text_metrics(text: &str) -> Value {
+    let mut sentences = 0;
+    let mut has_words = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !c.is_whitespace() && !matches!(c, '.' | '!' | '?') {
+            has_words = true;
+            continue;
+        }
+        if matches!(c, '.' | '!' | '?')
+            && has_words
+            && chars.peek().map_or(true, |next| next.is_whitespace())
+        {
+            sentences += 1;
+            has_words = false;
+        }
+    }
+    if has_words {
+        sentences += 1;
+    }
+    Value::from_iter([
+        ("chars".to_string(), Value::Int(text.chars().count() as i64)),
+        (
+            "words".to_string(),
+            Value::Int(text.split_whitespace().count() as i64),
+        ),
+        ("sentences".to_string(), Value::Int(sentences)),
+    ])
+}
+
+/// Splits the `srcset` attribute value into `(url, descriptor)` pairs.
+/// The url ends at the first whitespace, so urls containing commas (i.e. data urls) are preserved.
+fn parse_srcset(srcset: &str) -> Vec<(&str, &str)> {
+    let mut candidates = vec![];
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            break;
+        }
+        let url_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let url = &rest[..url_end];
+        rest = &rest[url_end..];
+        if url.ends_with(',') {
+            candidates.push((url.trim_end_matches(','), ""));
+            continue;
+        }
+        let descriptor_end = rest.find(',').unwrap_or(rest.len());
+        candidates.push((url, rest[..descriptor_end].trim()));
+        rest = &rest[descriptor_end..];
+    }
+    candidates
+}
+
+/// Returns the numeric value of the `srcset` descriptor, i.e. `2` for `2x` and `640` for `640w`.
+fn srcset_descriptor_value(descriptor: &str) -> f64 {
+    if descriptor.is_empty() {
+        return 1.0;
+    }
+    descriptor
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .parse()
+        .unwrap_or(0.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,6 +1995,33 @@ mod tests {
         assert_eq!(res, "http://www.example.com/");
     }
 
+    #[test]
+    fn regex_group_proc() {
+        let args = [
+            r"(?:https?://)(?<domain>[a-zA-Z0-9.-]+)/(?<path>\w+)?".to_string(),
+            "domain".to_string(),
+        ];
+        let proc = Proc::new(REGEX_GROUP_PROC, &args).unwrap();
+        assert_eq!(
+            proc.handle("http://www.example.com/p1/?q=2"),
+            "www.example.com"
+        );
+        assert_eq!(proc.handle("no url"), "");
+
+        let args = [args[0].clone(), "2".to_string()];
+        let proc = Proc::new(REGEX_GROUP_PROC, &args).unwrap();
+        assert_eq!(proc.handle("http://www.example.com/p1/?q=2"), "p1");
+        assert_eq!(proc.handle("http://www.example.com/"), "");
+
+        for group in ["missing", "3"] {
+            let args = [args[0].clone(), group.to_string()];
+            assert!(matches!(
+                Proc::new(REGEX_GROUP_PROC, &args),
+                Err(PipelineError::ProcInvalidArgument(_, _))
+            ));
+        }
+    }
+
     #[test]
     fn extract_json() {
         let proc = Proc::ExtractJson(Cow::from("a.b.c"));
@@ -253,10 +2041,687 @@ mod tests {
         let res = proc.handle("search/?q=mob%20100");
         assert_eq!(res, "search/?q=mob+100");
     }
+    #[test]
+    fn normalize_text() {
+        let proc = Proc::NormalizeText;
+        assert_eq!(
+            proc.handle("\u{FEFF}first\r\nsecond\rthird\n\r\n"),
+            "first\nsecond\nthird\n\n"
+        );
+        assert_eq!(proc.handle("a\u{FEFF}b\r\n"), "a\u{FEFF}b\n");
+    }
+
     #[test]
     fn normalize_spaces() {
         let proc = Proc::NormalizeSpaces;
         let res = proc.handle("<div>\n    Some\t</span>green</span>  text\n</div>\n");
         assert_eq!(res, "<div> Some </span>green</span> text </div>");
     }
+
+    #[test]
+    fn slugify() {
+        let proc = Proc::Slugify;
+        assert_eq!(proc.handle("Hello, World!"), "hello-world");
+        assert_eq!(proc.handle("Crème Brûlée!"), "creme-brulee");
+    }
+
+    #[test]
+    fn readable_text() {
+        let proc = Proc::ReadableText;
+        let res = proc.handle("<p>Caf&eacute;  &amp;  tea</p>");
+        assert_eq!(res, "Café & tea");
+        let res = proc.handle("\n<div>  <b>Bold</b>\t<i>text</i>&nbsp;</div>\n");
+        assert_eq!(res, "Bold text");
+        let res = proc.handle("<p>Fish &amp; <b>chips</b></p><script>x()</script><p>two</p>");
+        assert_eq!(res, "Fish & chips two");
+        let res =
+            proc.handle("<ul><li>One</li><li>Two<br>lines</li></ul><style>p{}</style><h2>End</h2>");
+        assert_eq!(res, "One Two lines End");
+    }
+
+    #[test]
+    fn first_sentence() {
+        let proc = Proc::FirstSentence;
+        let res = proc.handle("  Version 2.0 is out! Download it from the site.");
+        assert_eq!(res, "Version 2.0 is out!");
+        let res = proc.handle("No terminator here ");
+        assert_eq!(res, "No terminator here");
+    }
+
+    #[test]
+    fn first_words() {
+        let proc = Proc::FirstWords(3);
+        let res = proc.handle("The quick\n brown fox jumps");
+        assert_eq!(res, "The quick brown");
+    }
+
+    #[test]
+    fn numbers_with_units() {
+        let proc = TerminalProc::NumbersWithUnits;
+        let res = proc.handle("10.3mg 2g 0.5kg n/a");
+        assert_eq!(res, Value::from_iter(vec![10.3, 2.0, 0.5]));
+    }
+
+    #[test]
+    fn sum_numbers() {
+        let proc = Proc::new(SUM_NUMBERS, &[]).unwrap();
+        assert_eq!(proc.handle("12 apples, 8 oranges"), "20");
+        assert_eq!(proc.handle("1.5kg + 2kg"), "3.5");
+        assert_eq!(proc.handle("-3 and 5, pages 1-2"), "5");
+        assert_eq!(proc.handle("none"), "0");
+    }
+
+    #[test]
+    fn terminal_proc_must_be_last() {
+        let raw = vec![
+            vec!["numbers_with_units".to_string()],
+            vec!["trim_space".to_string()],
+        ];
+        assert!(Pipeline::new(&raw).is_err());
+    }
+
+    #[test]
+    fn between() {
+        let proc = Proc::Between(Cow::from("var data = "), Cow::from(";"));
+        let res = proc.handle(r#"<script>var data = {"a": 1};</script>"#);
+        assert_eq!(res, r#"{"a": 1}"#);
+        let res = proc.handle("var other = 1;");
+        assert_eq!(res, "");
+    }
+
+    #[test]
+    fn srcset_all() {
+        let proc = TerminalProc::Srcset(SrcsetMode::All);
+        let res = proc.handle("a.jpg, data:image/png;base64,AAA= 2x,c.jpg 640w");
+        let urls: Option<Vec<String>> = res.from_path("#.url").and_then(|v| v.into());
+        let descriptors: Option<Vec<String>> = res.from_path("#.descriptor").and_then(|v| v.into());
+        assert_eq!(
+            urls.unwrap(),
+            vec!["a.jpg", "data:image/png;base64,AAA=", "c.jpg"]
+        );
+        assert_eq!(descriptors.unwrap(), vec!["", "2x", "640w"]);
+    }
+
+    #[test]
+    fn pick_largest_image() {
+        let proc = TerminalProc::new(PICK_LARGEST_IMAGE, &[]).unwrap().unwrap();
+        let res = proc.handle("small.jpg 480w, large.jpg 1200w, medium.jpg 800w");
+        assert_eq!(res, Value::from("large.jpg"));
+        assert_eq!(proc.handle("a.jpg 2x, b.jpg"), Value::Null);
+        assert_eq!(proc.name(), PICK_LARGEST_IMAGE);
+    }
+
+    #[test]
+    fn when_match() {
+        let raw = vec![vec![
+            "when_match".to_string(),
+            "^http:".to_string(),
+            "replace".to_string(),
+            "http:".to_string(),
+            "https:".to_string(),
+        ]];
+        let pipeline = Pipeline::new(&raw).unwrap();
+        let res = pipeline.handle("http://example.com/http:".to_string());
+        assert!(matches!(res, PipelineOutput::Scalar(s) if s == "https://example.com/https:"));
+        let res = pipeline.handle("ftp://example.com/http:".to_string());
+        assert!(matches!(res, PipelineOutput::Scalar(s) if s == "ftp://example.com/http:"));
+    }
+
+    #[test]
+    fn ratio() {
+        let proc = Proc::Ratio;
+        assert_eq!(proc.handle("3/4"), "0.75");
+        assert_eq!(proc.handle(" 10 / 4 "), "2.5");
+        assert_eq!(proc.handle("1/0"), "");
+        assert_eq!(proc.handle("n/a"), "");
+    }
+
+    #[test]
+    #[cfg(feature = "unicode_categories")]
+    fn keep_categories() {
+        let raw = vec![vec![
+            "keep_categories".to_string(),
+            "L".to_string(),
+            "N".to_string(),
+            "P".to_string(),
+            "Zs".to_string(),
+        ]];
+        let pipeline = Pipeline::new(&raw).unwrap();
+        let res = pipeline.handle("hi 😀 there".to_string());
+        assert!(matches!(res, PipelineOutput::Scalar(ref s) if s == "hi  there"));
+
+        let raw = vec![raw[0].clone(), vec!["normalize_spaces".to_string()]];
+        let pipeline = Pipeline::new(&raw).unwrap();
+        let res = pipeline.handle("hi 😀 there!".to_string());
+        assert!(matches!(res, PipelineOutput::Scalar(ref s) if s == "hi there!"));
+    }
+
+    #[test]
+    #[cfg(feature = "unicode_categories")]
+    fn keep_categories_invalid_category() {
+        let raw = vec![vec!["keep_categories".to_string(), "Xx".to_string()]];
+        assert!(Pipeline::new(&raw).is_err());
+    }
+
+    #[test]
+    fn short_hash() {
+        let proc = Proc::ShortHash(8);
+        let key = proc.handle("https://example.com/a");
+        assert_eq!(key.len(), 8);
+        assert_eq!(key, proc.handle("https://example.com/a"));
+        assert_ne!(key, proc.handle("https://example.com/b"));
+        assert_eq!(Proc::ShortHash(4).handle("https://example.com/a"), key[4..]);
+    }
+
+    #[test]
+    fn check_shape() {
+        let proc = Proc::new(
+            CHECK_SHAPE,
+            &["object:{id:int, name:string, tags:array:[string]}".to_string()],
+        )
+        .unwrap();
+        let valid = r#"{"id":1,"name":"Apple","tags":["fruit"],"extra":null}"#;
+        assert_eq!(proc.handle(valid), valid);
+        assert_eq!(proc.handle(r#"{"id":1.5,"name":"Apple","tags":[]}"#), "");
+        assert_eq!(proc.handle(r#"{"id":1,"tags":[]}"#), "");
+        assert_eq!(proc.handle(r#"{"id":1,"name":"Apple","tags":[1]}"#), "");
+        assert_eq!(proc.handle("not a json"), "");
+    }
+
+    #[test]
+    fn check_shape_invalid_spec() {
+        for spec in [
+            "object:{id:int",
+            "array:[int",
+            "integer",
+            "object:{:int}",
+            "int,",
+        ] {
+            let res = Proc::new(CHECK_SHAPE, &[spec.to_string()]);
+            assert!(
+                matches!(res, Err(PipelineError::ProcInvalidArgument(_, _))),
+                "{}",
+                spec
+            );
+        }
+    }
+
+    #[cfg(feature = "timezones")]
+    #[test]
+    fn to_utc() {
+        let proc = Proc::new(TO_UTC, &["%Y-%m-%d %H:%M %:z".to_string()]).unwrap();
+        assert_eq!(
+            proc.handle("2023-06-01 12:00 +02:00"),
+            "2023-06-01T10:00:00Z"
+        );
+        assert_eq!(proc.handle("2023-06-01 12:00"), "");
+
+        let args = ["%Y-%m-%d %H:%M".to_string(), "Europe/Berlin".to_string()];
+        let proc = Proc::new(TO_UTC, &args).unwrap();
+        assert_eq!(proc.handle("2023-01-15 12:00"), "2023-01-15T11:00:00Z");
+        assert_eq!(proc.handle("2023-06-01 12:00"), "2023-06-01T10:00:00Z");
+        // skipped by the DST transition
+        assert_eq!(proc.handle("2023-03-26 02:30"), "");
+        // occurs twice, the earliest instant is taken
+        assert_eq!(proc.handle("2023-10-29 02:30"), "2023-10-29T00:30:00Z");
+
+        let args = ["%d.%m.%Y".to_string(), "-05:00".to_string()];
+        let proc = Proc::new(TO_UTC, &args).unwrap();
+        assert_eq!(proc.handle("01.06.2023"), "2023-06-01T05:00:00Z");
+    }
+
+    #[cfg(feature = "timezones")]
+    #[test]
+    fn humanize_time() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let proc = Proc::new(HUMANIZE_TIME, &["epoch".to_string()]).unwrap();
+        let three_days_ago = now - 3 * 86400 - 60;
+        assert_eq!(proc.handle(&three_days_ago.to_string()), "3 days ago");
+        assert_eq!(proc.handle("not a timestamp"), "");
+
+        // 2023-06-01T10:00:00Z
+        let now = 1_685_613_600;
+        let cases = [
+            ("2023-06-01 09:59:30 +00:00", "just now"),
+            ("2023-06-01 11:59:00 +02:00", "1 minute ago"),
+            ("2023-06-01 07:00:00 +00:00", "3 hours ago"),
+            ("2023-05-29 10:00:00 +00:00", "3 days ago"),
+            ("2022-06-01 10:00:00 +00:00", "1 year ago"),
+            ("2023-06-15 10:00:00 +00:00", "in 2 weeks"),
+        ];
+        for (input, expected) in cases {
+            let res = super::humanize_time(input, "%Y-%m-%d %H:%M:%S %:z", now);
+            assert_eq!(res.unwrap(), expected, "{}", input);
+        }
+        let res = super::humanize_time("2023-05-31 10:00", "%Y-%m-%d %H:%M", now);
+        assert_eq!(res.unwrap(), "1 day ago");
+    }
+
+    #[cfg(feature = "timezones")]
+    #[test]
+    fn to_utc_invalid_timezone() {
+        let args = ["%Y-%m-%d".to_string(), "Mars/Olympus".to_string()];
+        let res = Proc::new(TO_UTC, &args);
+        assert!(matches!(res, Err(PipelineError::ProcInvalidArgument(_, _))));
+    }
+
+    #[test]
+    fn csv_cell() {
+        let proc = Proc::CsvCell;
+        assert_eq!(proc.handle("plain value"), "plain value");
+        assert_eq!(proc.handle("Apple, Inc."), r#""Apple, Inc.""#);
+        assert_eq!(proc.handle(r#"12" pizza"#), r#""12"" pizza""#);
+        assert_eq!(proc.handle("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn clean_tokens() {
+        let proc = Proc::new(CLEAN_TOKENS, &[",".to_string()]).unwrap();
+        assert_eq!(proc.handle(" a ,b, a ,,c "), "a,b,c");
+        assert_eq!(proc.handle(" , "), "");
+        let proc = Proc::new(CLEAN_TOKENS, &[" ".to_string()]).unwrap();
+        assert_eq!(proc.handle("btn  btn-primary btn "), "btn btn-primary");
+        assert!(matches!(
+            Proc::new(CLEAN_TOKENS, &["".to_string()]),
+            Err(PipelineError::ProcInvalidArgument(_, _))
+        ));
+    }
+
+    #[test]
+    fn decl_params() {
+        let proc = TerminalProc::DeclParams;
+        let res = proc.handle("color: red; font-weight: bold");
+        let expected = Value::from_iter([
+            ("color".to_string(), Value::from("red")),
+            ("font-weight".to_string(), Value::from("bold")),
+        ]);
+        assert_eq!(res, expected);
+
+        let res = proc.handle(" background: url(https://example.com/a.png);;invalid; :empty; color: red; color: blue ");
+        let expected = Value::from_iter([
+            (
+                "background".to_string(),
+                Value::from("url(https://example.com/a.png)"),
+            ),
+            ("color".to_string(), Value::from("blue")),
+        ]);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn text_metrics() {
+        let proc = TerminalProc::TextMetrics;
+        let metrics = |chars: i64, words: i64, sentences: i64| {
+            Value::from_iter([
+                ("chars".to_string(), Value::Int(chars)),
+                ("words".to_string(), Value::Int(words)),
+                ("sentences".to_string(), Value::Int(sentences)),
+            ])
+        };
+        let text = "Apples are sweet. Are they healthy?! Yes... They contain 10.3mg of vitamin C";
+        assert_eq!(proc.handle(text), metrics(76, 13, 4));
+        assert_eq!(proc.handle(""), metrics(0, 0, 0));
+        assert_eq!(proc.handle("  ... "), metrics(6, 1, 0));
+    }
+
+    #[cfg(feature = "urls")]
+    #[test]
+    fn canonical_url() {
+        let proc = Proc::new(CANONICAL_URL, &[]).unwrap();
+        assert_eq!(
+            proc.handle("https://x.com/a?utm_source=y&b=1#top"),
+            "https://x.com/a?b=1"
+        );
+        assert_eq!(
+            proc.handle("HTTPS://X.com:443/a?z=2&gclid=abc&a=1&fbclid=def"),
+            "https://x.com/a?a=1&z=2"
+        );
+        assert_eq!(proc.handle("/relative/path"), "");
+        assert_eq!(
+            proc.handle_with_base("/relative/path#top", Some("https://x.com/dir/")),
+            "https://x.com/relative/path"
+        );
+
+        let proc = Proc::new(CANONICAL_URL, &["ref".to_string()]).unwrap();
+        assert_eq!(
+            proc.handle("https://x.com/a?ref=main&utm_source=y"),
+            "https://x.com/a?utm_source=y"
+        );
+    }
+
+    #[cfg(feature = "urls")]
+    #[test]
+    fn resolve_url() {
+        let proc = Proc::new(RESOLVE_URL, &["https://example.com/x/y".to_string()]).unwrap();
+        assert_eq!(proc.handle("/a/b"), "https://example.com/a/b");
+        assert_eq!(proc.handle("../c"), "https://example.com/c");
+        assert_eq!(proc.handle("z"), "https://example.com/x/z");
+        assert_eq!(proc.handle("https://other.com/d"), "https://other.com/d");
+
+        assert_eq!(
+            proc.handle_with_base("a", Some("https://x.com/dir/")),
+            "https://x.com/dir/a"
+        );
+        assert_eq!(
+            proc.handle_with_base("a", Some("/dir/")),
+            "https://example.com/dir/a"
+        );
+
+        let proc = Proc::new(RESOLVE_URL, &["".to_string()]).unwrap();
+        assert_eq!(proc.handle("/a/b"), "/a/b");
+        assert_eq!(
+            proc.handle_with_base("/a/b", Some("https://x.com/dir/")),
+            "https://x.com/a/b"
+        );
+
+        let res = Proc::new(RESOLVE_URL, &["/relative".to_string()]);
+        assert!(matches!(res, Err(PipelineError::ProcInvalidArgument(_, _))));
+    }
+
+    #[test]
+    fn titlecase() {
+        let proc = Proc::new(TITLECASE, &[]).unwrap();
+        assert_eq!(proc.handle("hELLO   world"), "Hello   World");
+        assert_eq!(
+            proc.handle("  «the» o'NEIL-smith  "),
+            "  «The» O'neil-smith  "
+        );
+        assert_eq!(proc.handle(""), "");
+    }
+
+    #[cfg(feature = "lang_detect")]
+    #[test]
+    fn detect_lang() {
+        let proc = Proc::new(DETECT_LANG, &[]).unwrap();
+        assert_eq!(
+            proc.handle("The quick brown fox jumps over the lazy dog near the river bank."),
+            "en"
+        );
+        assert_eq!(
+            proc.handle("Der schnelle braune Fuchs springt über den faulen Hund am Flussufer."),
+            "de"
+        );
+        assert_eq!(proc.handle("42"), "");
+    }
+
+    #[test]
+    fn contains_ci() {
+        let proc = TerminalProc::new(CONTAINS_CI, &["In Stock".to_string()])
+            .unwrap()
+            .unwrap();
+        assert_eq!(proc.handle("Status: IN STOCK now"), Value::Bool(true));
+        assert_eq!(proc.handle("status: in stock"), Value::Bool(true));
+        assert_eq!(proc.handle("Out of stock"), Value::Bool(false));
+        assert_eq!(proc.handle(""), Value::Bool(false));
+    }
+
+    #[test]
+    fn split() {
+        let proc = TerminalProc::new(SPLIT, &["|".to_string()])
+            .unwrap()
+            .unwrap();
+        assert_eq!(proc.handle("a|b||c"), Value::from_iter(["a", "b", "", "c"]));
+        assert_eq!(proc.handle(""), Value::Array(vec![]));
+        assert!(TerminalProc::new(SPLIT, &[]).is_err());
+        assert!(matches!(
+            TerminalProc::new(SPLIT, &["".to_string()]),
+            Err(PipelineError::ProcInvalidArgument(_, _))
+        ));
+    }
+
+    #[test]
+    fn bucket() {
+        let args = ["0-2:low", "3-4:mid", "5:high", "-10--1:negative"].map(String::from);
+        let proc = Proc::new(BUCKET, &args).unwrap();
+        assert_eq!(proc.handle("4"), "mid");
+        assert_eq!(proc.handle(" 0 "), "low");
+        assert_eq!(proc.handle("5"), "high");
+        assert_eq!(proc.handle("-3.5"), "negative");
+        assert_eq!(proc.handle("4.5"), "");
+        assert_eq!(proc.handle("n/a"), "");
+
+        for arg in ["low", "a-b:low", "5-3:low"] {
+            let res = Proc::new(BUCKET, &[arg.to_string()]);
+            assert!(matches!(res, Err(PipelineError::ProcInvalidArgument(_, _))));
+        }
+    }
+
+    #[test]
+    fn url_decode() {
+        let proc = Proc::new(URL_DECODE, &[]).unwrap();
+        assert_eq!(
+            proc.handle("search%2Fq%3Dmob%2520100"),
+            "search/q=mob%20100"
+        );
+        assert_eq!(proc.handle("a+b%20c"), "a+b c");
+        assert_eq!(proc.handle("100%, %zz, %+1, %4"), "100%, %zz, %+1, %4");
+        assert_eq!(proc.handle("%D0%BF%D1%80%D0%B8"), "при");
+
+        let proc = Proc::new(URL_DECODE, &["plus".to_string()]).unwrap();
+        assert_eq!(proc.handle("a+b%20c%2B"), "a b c+");
+
+        assert!(Proc::new(URL_DECODE, &["minus".to_string()]).is_err());
+    }
+
+    #[test]
+    fn regex_replace() {
+        let args = [
+            r"(\d{4})-(\d{2})-(\d{2})".to_string(),
+            "$3/$2/$1".to_string(),
+        ];
+        let proc = Proc::new(REGEX_REPLACE_PROC, &args).unwrap();
+        assert_eq!(proc.handle("2021-05-01"), "01/05/2021");
+        assert_eq!(
+            proc.handle("from 2021-05-01 to 2021-06-30"),
+            "from 01/05/2021 to 30/06/2021"
+        );
+
+        let args = [r"(?<word>\w+)@".to_string(), "${word} at ".to_string()];
+        let proc = Proc::new(REGEX_REPLACE_PROC, &args).unwrap();
+        assert_eq!(proc.handle("john@ jane@"), "john at  jane at ");
+    }
+
+    #[test]
+    fn regex_all() {
+        let text = "Call +1 555 0100, +1 555 0101 or +1 555 0102.";
+        let proc = Proc::new(REGEX_ALL_PROC, &[r"\+\d[\d ]+\d".to_string()]).unwrap();
+        assert_eq!(proc.handle(text), "+1 555 0100\n+1 555 0101\n+1 555 0102");
+
+        let args = [r"\+\d[\d ]+\d".to_string(), ", ".to_string()];
+        let proc = Proc::new(REGEX_ALL_PROC, &args).unwrap();
+        assert_eq!(proc.handle(text), "+1 555 0100, +1 555 0101, +1 555 0102");
+        assert_eq!(proc.handle("no phones"), "");
+    }
+
+    #[test]
+    fn money() {
+        let proc = Proc::new(MONEY, &[]).unwrap();
+        assert_eq!(proc.handle("5"), "5.00");
+        assert_eq!(proc.handle("5.1"), "5.10");
+        assert_eq!(proc.handle("5.126"), "5.13");
+        assert_eq!(proc.handle("1.005"), "1.01");
+        assert_eq!(proc.handle("99.995"), "100.00");
+        assert_eq!(proc.handle("-0.004"), "0.00");
+        assert_eq!(proc.handle("-2.345"), "-2.35");
+        assert_eq!(proc.handle(".5"), "0.50");
+        assert_eq!(proc.handle("1e3"), "1000.00");
+        assert_eq!(proc.handle("$5"), "");
+
+        let proc = Proc::new(MONEY, &["0".to_string()]).unwrap();
+        assert_eq!(proc.handle("9.5"), "10");
+        assert!(Proc::new(MONEY, &["two".to_string()]).is_err());
+    }
+
+    #[test]
+    fn html_escape() {
+        let proc = Proc::new(HTML_ESCAPE, &[]).unwrap();
+        let escaped = proc.handle("<a> & 'b'");
+        assert_eq!(escaped, "&lt;a&gt; &amp; 'b'");
+        assert_eq!(Proc::HtmlUnescape.handle(&escaped), "<a> & 'b'");
+    }
+
+    #[test]
+    fn parse_bytes() {
+        let proc = Proc::new(PARSE_BYTES, &[]).unwrap();
+        assert_eq!(proc.handle("1.5 MB"), "1500000");
+        assert_eq!(proc.handle("1 KiB"), "1024");
+        assert_eq!(proc.handle("2gib"), "2147483648");
+        assert_eq!(proc.handle("512 bytes"), "512");
+        assert_eq!(proc.handle("42"), "42");
+        assert_eq!(proc.handle("1.5 XB"), "");
+        assert_eq!(proc.handle("MB"), "");
+    }
+
+    #[test]
+    fn prepend() {
+        let proc = Proc::new(PREPEND, &["https://example.com".to_string()]).unwrap();
+        assert_eq!(proc.handle("/page"), "https://example.com/page");
+        assert!(Proc::new(PREPEND, &[]).is_err());
+    }
+
+    #[test]
+    fn append() {
+        let proc = Proc::new(APPEND, &["?lang=en".to_string()]).unwrap();
+        assert_eq!(proc.handle("/page"), "/page?lang=en");
+        assert!(Proc::new(APPEND, &[]).is_err());
+    }
+
+    #[test]
+    fn default() {
+        let proc = Proc::new(DEFAULT, &["N/A".to_string()]).unwrap();
+        assert_eq!(proc.handle(""), "N/A");
+        assert_eq!(proc.handle(" \n\t"), "N/A");
+        assert_eq!(proc.handle(" value "), " value ");
+    }
+
+    #[test]
+    fn coalesce() {
+        let args = [
+            "".to_string(),
+            " ".to_string(),
+            "N/A".to_string(),
+            "-".to_string(),
+        ];
+        let proc = Proc::new(COALESCE, &args).unwrap();
+        assert_eq!(proc.handle(" value "), " value ");
+        assert_eq!(proc.handle(" \n"), "N/A");
+
+        let proc = Proc::new(COALESCE, &["".to_string(), "  ".to_string()]).unwrap();
+        assert_eq!(proc.handle(""), "");
+
+        assert!(matches!(
+            Proc::new(COALESCE, &[]),
+            Err(PipelineError::ProcNotEnoughArguments(_, _, _))
+        ));
+    }
+
+    #[test]
+    fn printf() {
+        let cases = [
+            ("%.2f", "3.14159", "3.14"),
+            ("%08.2f", "3.14159", "00003.14"),
+            ("%08.2f", "-3.14159", "-0003.14"),
+            ("%+.1f", "2", "+2.0"),
+            ("%-6d|", "42.9", "42    |"),
+            ("%5d", "-42", "  -42"),
+            ("%f", "1", "1.000000"),
+            ("$%.2f (100%%)", "5", "$5.00 (100%)"),
+            ("%.2f", "n/a", "n/a"),
+        ];
+        for (spec, input, expected) in cases {
+            let proc = Proc::new(PRINTF, &[spec.to_string()]).unwrap();
+            assert_eq!(proc.handle(input), expected, "{}", spec);
+        }
+
+        for spec in ["no conversion", "%s", "%.2f %d", "%.f%", "100%%"] {
+            let res = Proc::new(PRINTF, &[spec.to_string()]);
+            assert!(
+                matches!(res, Err(PipelineError::ProcInvalidArgument(_, _))),
+                "{}",
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn ordinal() {
+        let proc = Proc::Ordinal;
+        let cases = [
+            ("1", "1st"),
+            ("2", "2nd"),
+            ("3", "3rd"),
+            ("4", "4th"),
+            ("11", "11th"),
+            ("12", "12th"),
+            ("13", "13th"),
+            ("22", "22nd"),
+            ("113", "113th"),
+            ("-21", "-21st"),
+            ("1.5", "1.5"),
+            ("first", "first"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(proc.handle(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn slice() {
+        let cases: [(&[&str], &str); 8] = [
+            (&["0", "4"], "SKU-"),
+            (&["4"], "1234-XL"),
+            (&["-2"], "XL"),
+            (&["-7", "-3"], "1234"),
+            (&["4", "100"], "1234-XL"),
+            (&["-100", "3"], "SKU"),
+            (&["5", "2"], ""),
+            (&["20"], ""),
+        ];
+        for (args, expected) in cases {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            let proc = Proc::new(SLICE, &args).unwrap();
+            assert_eq!(proc.handle("SKU-1234-XL"), expected, "{:?}", args);
+        }
+        let proc = Proc::new(SLICE, &["1".to_string(), "3".to_string()]).unwrap();
+        assert_eq!(proc.handle("äöüß"), "öü");
+
+        let res = Proc::new(SLICE, &["a".to_string()]);
+        assert!(matches!(res, Err(PipelineError::ProcInvalidArgument(_, _))));
+    }
+
+    #[cfg(feature = "hashing")]
+    #[test]
+    fn hash() {
+        let cases = [
+            (
+                "sha256",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            ("sha1", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            ("md5", "900150983cd24fb0d6963f7d28e17f72"),
+        ];
+        for (algo, expected) in cases {
+            let proc = Proc::new(HASH, &[algo.to_string()]).unwrap();
+            assert_eq!(proc.handle("abc"), expected, "{}", algo);
+        }
+
+        let res = Proc::new(HASH, &["crc32".to_string()]);
+        assert!(matches!(res, Err(PipelineError::ProcInvalidArgument(_, _))));
+    }
+
+    #[cfg(feature = "markdown")]
+    #[test]
+    fn markdown() {
+        let proc = Proc::new(MARKDOWN, &[]).unwrap();
+        assert_eq!(
+            proc.handle("<h1>Title</h1><p><b>hi</b></p>"),
+            "# Title\n\n**hi**"
+        );
+
+        let proc = Proc::new(TO_MARKDOWN, &[]).unwrap();
+        assert_eq!(proc.handle("<b>x</b> and <i>y</i>"), "**x** and _y_");
+    }
 }
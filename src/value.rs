@@ -1,11 +1,12 @@
 use std::convert::From;
 use std::iter::FromIterator;
 
-use hashbrown::HashMap;
+use hashbrown::DefaultHashBuilder;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 ///Value is a enum that can be used to store any basic type of data
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Value {
     Int(i64),
@@ -17,7 +18,8 @@ pub enum Value {
     Null,
 }
 
-pub type InnerMap = HashMap<String, Value>;
+/// The map of the `Value::Object`. It preserves the insertion order of the keys.
+pub type InnerMap = IndexMap<String, Value, DefaultHashBuilder>;
 
 impl From<i64> for Value {
     fn from(item: i64) -> Self {
@@ -140,4 +142,136 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Returns the sum of a numeric array. The result is `Value::Int` if all elements are integers
+    /// and the sum does not overflow, otherwise it is `Value::Float`.
+    /// Returns `None` if the value is not a non-empty numeric array.
+    pub fn sum(&self) -> Option<Value> {
+        match self.numeric_items()? {
+            Numbers::Int(items) => match items.iter().try_fold(0_i64, |acc, v| acc.checked_add(*v))
+            {
+                Some(sum) => Some(Value::Int(sum)),
+                None => Some(Value::Float(items.iter().map(|v| *v as f64).sum())),
+            },
+            Numbers::Float(items) => Some(Value::Float(items.iter().sum())),
+        }
+    }
+
+    /// Returns the arithmetic mean of a numeric array as `Value::Float`.
+    /// Returns `None` if the value is not a non-empty numeric array.
+    pub fn avg(&self) -> Option<Value> {
+        let (sum, len) = match self.numeric_items()? {
+            Numbers::Int(items) => (items.iter().map(|v| *v as f64).sum::<f64>(), items.len()),
+            Numbers::Float(items) => (items.iter().sum::<f64>(), items.len()),
+        };
+        Some(Value::Float(sum / len as f64))
+    }
+
+    /// Returns the minimum of a numeric array. Returns `None` if the value is not a non-empty numeric array.
+    pub fn min(&self) -> Option<Value> {
+        match self.numeric_items()? {
+            Numbers::Int(items) => items.into_iter().min().map(Value::Int),
+            Numbers::Float(items) => items.into_iter().reduce(f64::min).map(Value::Float),
+        }
+    }
+
+    /// Returns the maximum of a numeric array. Returns `None` if the value is not a non-empty numeric array.
+    pub fn max(&self) -> Option<Value> {
+        match self.numeric_items()? {
+            Numbers::Int(items) => items.into_iter().max().map(Value::Int),
+            Numbers::Float(items) => items.into_iter().reduce(f64::max).map(Value::Float),
+        }
+    }
+
+    /// Collects the elements of a non-empty numeric array. Integers are converted to floats
+    /// if the array contains at least one float.
+    fn numeric_items(&self) -> Option<Numbers> {
+        let Self::Array(arr) = self else {
+            return None;
+        };
+        if arr.is_empty() {
+            return None;
+        }
+        if arr.iter().all(|v| matches!(v, Self::Int(_))) {
+            let items = arr.iter().filter_map(|v| match v {
+                Self::Int(val) => Some(*val),
+                _ => None,
+            });
+            return Some(Numbers::Int(items.collect()));
+        }
+        arr.iter()
+            .map(|v| match v {
+                Self::Int(val) => Some(*val as f64),
+                Self::Float(val) => Some(*val),
+                _ => None,
+            })
+            .collect::<Option<Vec<f64>>>()
+            .map(Numbers::Float)
+    }
+}
+
+enum Numbers {
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_int_array() {
+        let val = Value::from_iter([3_i64, 1, 2]);
+        assert_eq!(val.sum(), Some(Value::Int(6)));
+        assert_eq!(val.avg(), Some(Value::Float(2.0)));
+        assert_eq!(val.min(), Some(Value::Int(1)));
+        assert_eq!(val.max(), Some(Value::Int(3)));
+    }
+
+    #[test]
+    fn aggregate_float_array() {
+        let val = Value::from_iter([1.5, 0.5, 2.5]);
+        assert_eq!(val.sum(), Some(Value::Float(4.5)));
+        assert_eq!(val.avg(), Some(Value::Float(1.5)));
+        assert_eq!(val.min(), Some(Value::Float(0.5)));
+        assert_eq!(val.max(), Some(Value::Float(2.5)));
+    }
+
+    #[test]
+    fn aggregate_mixed_array() {
+        let val = Value::Array(vec![Value::Int(1), Value::Float(2.5)]);
+        assert_eq!(val.sum(), Some(Value::Float(3.5)));
+        assert_eq!(val.max(), Some(Value::Float(2.5)));
+    }
+
+    #[test]
+    fn aggregate_non_numeric() {
+        let val = Value::from_iter(["1", "2"]);
+        assert_eq!(val.sum(), None);
+        assert_eq!(val.avg(), None);
+        assert_eq!(val.min(), None);
+        assert_eq!(val.max(), None);
+        assert_eq!(Value::Array(vec![]).sum(), None);
+        assert_eq!(Value::Int(1).sum(), None);
+    }
+
+    #[test]
+    fn object_into_pairs() {
+        let val = Value::from_iter([
+            ("title".to_string(), Value::from("Apple")),
+            ("calories".to_string(), Value::Int(52)),
+            ("tags".to_string(), Value::from_iter(["fruit", "red"])),
+        ]);
+        let pairs: Option<Vec<(String, Value)>> = (&val).into();
+        let pairs = pairs.unwrap();
+        let keys: Vec<&str> = pairs.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["title", "calories", "tags"]);
+        assert_eq!(Value::from_iter(pairs), val);
+
+        let pairs: Option<Vec<(String, Value)>> = val.clone().into();
+        assert_eq!(Value::from_iter(pairs.unwrap()), val);
+
+        let pairs: Option<Vec<(String, Value)>> = Value::from("Apple").into();
+        assert!(pairs.is_none());
+    }
 }
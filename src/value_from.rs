@@ -153,4 +153,22 @@ impl From<&Value> for Option<Vec<bool>> {
     }
 }
 
+impl From<Value> for Option<Vec<(String, Value)>> {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Object(val) => Some(val.into_iter().collect()),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Value> for Option<Vec<(String, Value)>> {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Object(val) => Some(val.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+            _ => None,
+        }
+    }
+}
+
 // TODO: add From<Value> for Option<HashMap<String, String>> and so on.
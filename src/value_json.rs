@@ -0,0 +1,285 @@
+use std::fmt::Write;
+
+use super::value::{InnerMap, Value};
+
+const INDENT: &str = "  ";
+
+/// `SerializeOptions` controls how `Value::to_json_string_with` serializes the value.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SerializeOptions {
+    /// if it is `true` then object entries with `null` values are omitted.
+    pub omit_nulls: bool,
+    /// if it is `true` then object entries with empty arrays or objects are omitted.
+    /// An object is also considered empty if all of its entries are omitted.
+    pub omit_empty: bool,
+    /// if it is `true` then the output is indented with two spaces.
+    pub pretty: bool,
+}
+
+impl Value {
+    /// Serializes the value into a JSON string according to the given `SerializeOptions`.
+    /// Omission rules are applied only to object entries, array elements are always kept to preserve their positions.
+    /// Non-finite floats are serialized as `null`.
+    ///
+    /// # Examples
+    /// ```
+    /// use dom_finder::{SerializeOptions, Value};
+    /// let val = Value::from_iter([
+    ///     ("title".to_string(), Value::from("Apple")),
+    ///     ("rating".to_string(), Value::Null),
+    /// ]);
+    /// let opts = SerializeOptions {
+    ///     omit_nulls: true,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(val.to_json_string_with(opts), r#"{"title":"Apple"}"#);
+    /// ```
+    pub fn to_json_string_with(&self, opts: SerializeOptions) -> String {
+        let mut buf = String::new();
+        write_value(&mut buf, self, &opts, 0);
+        buf
+    }
+}
+
+/// Parses the JSON string into a `Value`. Numbers without a fraction and an exponent that fit into `i64`
+/// become `Value::Int`, other numbers become `Value::Float`. Returns `None` if the string is not a valid JSON.
+pub(crate) fn value_from_json(json: &str) -> Option<Value> {
+    if !gjson::valid(json) {
+        return None;
+    }
+    Some(value_from_gjson(&gjson::parse(json)))
+}
+
+fn value_from_gjson(json: &gjson::Value) -> Value {
+    match json.kind() {
+        gjson::Kind::Null => Value::Null,
+        gjson::Kind::True => Value::Bool(true),
+        gjson::Kind::False => Value::Bool(false),
+        gjson::Kind::Number => match json.json().parse::<i64>() {
+            Ok(val) => Value::Int(val),
+            Err(_) => Value::Float(json.f64()),
+        },
+        gjson::Kind::String => Value::String(json.str().to_string()),
+        gjson::Kind::Array => json.array().iter().map(value_from_gjson).collect(),
+        gjson::Kind::Object => {
+            let mut m = InnerMap::default();
+            json.each(|key, val| {
+                m.insert(key.str().to_string(), value_from_gjson(&val));
+                true
+            });
+            Value::Object(m)
+        }
+    }
+}
+
+fn is_omitted(value: &Value, opts: &SerializeOptions) -> bool {
+    match value {
+        Value::Null => opts.omit_nulls,
+        Value::Array(arr) => opts.omit_empty && arr.is_empty(),
+        Value::Object(obj) => opts.omit_empty && obj.values().all(|v| is_omitted(v, opts)),
+        _ => false,
+    }
+}
+
+fn write_value(buf: &mut String, value: &Value, opts: &SerializeOptions, depth: usize) {
+    match value {
+        Value::Null => buf.push_str("null"),
+        Value::Bool(val) => buf.push_str(if *val { "true" } else { "false" }),
+        Value::Int(val) => {
+            let _ = write!(buf, "{}", val);
+        }
+        Value::Float(val) if val.is_finite() => {
+            let _ = write!(buf, "{:?}", val);
+        }
+        Value::Float(_) => buf.push_str("null"),
+        Value::String(val) => write_string(buf, val),
+        Value::Array(arr) => {
+            buf.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                write_newline(buf, opts, depth + 1);
+                write_value(buf, item, opts, depth + 1);
+            }
+            if !arr.is_empty() {
+                write_newline(buf, opts, depth);
+            }
+            buf.push(']');
+        }
+        Value::Object(obj) => {
+            buf.push('{');
+            let mut is_first = true;
+            for (key, item) in obj.iter().filter(|(_, v)| !is_omitted(v, opts)) {
+                if !is_first {
+                    buf.push(',');
+                }
+                is_first = false;
+                write_newline(buf, opts, depth + 1);
+                write_string(buf, key);
+                buf.push(':');
+                if opts.pretty {
+                    buf.push(' ');
+                }
+                write_value(buf, item, opts, depth + 1);
+            }
+            if !is_first {
+                write_newline(buf, opts, depth);
+            }
+            buf.push('}');
+        }
+    }
+}
+
+fn write_newline(buf: &mut String, opts: &SerializeOptions, depth: usize) {
+    if opts.pretty {
+        buf.push('\n');
+        for _ in 0..depth {
+            buf.push_str(INDENT);
+        }
+    }
+}
+
+fn write_string(buf: &mut String, s: &str) {
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(buf, "\\u{:04x}", c as u32);
+            }
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixed_value() -> Value {
+        Value::from_iter([
+            ("title".to_string(), Value::from("Say \"hi\"")),
+            ("rating".to_string(), Value::Null),
+            ("tags".to_string(), Value::Array(vec![])),
+            (
+                "meta".to_string(),
+                Value::from_iter([("author".to_string(), Value::Null)]),
+            ),
+            (
+                "prices".to_string(),
+                Value::Array(vec![Value::Float(1.5), Value::Null]),
+            ),
+        ])
+    }
+
+    fn parse_json(s: &str) -> serde_json::Value {
+        serde_json::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn serialize_default() {
+        let res = mixed_value().to_json_string_with(SerializeOptions::default());
+        assert!(!res.contains('\n'));
+        assert_eq!(
+            parse_json(&res),
+            serde_json::json!({
+                "title": "Say \"hi\"",
+                "rating": null,
+                "tags": [],
+                "meta": {"author": null},
+                "prices": [1.5, null],
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_omit_nulls() {
+        let opts = SerializeOptions {
+            omit_nulls: true,
+            ..Default::default()
+        };
+        let res = mixed_value().to_json_string_with(opts);
+        assert_eq!(
+            parse_json(&res),
+            serde_json::json!({
+                "title": "Say \"hi\"",
+                "tags": [],
+                "meta": {},
+                "prices": [1.5, null],
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_omit_empty() {
+        let opts = SerializeOptions {
+            omit_empty: true,
+            ..Default::default()
+        };
+        let res = mixed_value().to_json_string_with(opts);
+        assert_eq!(
+            parse_json(&res),
+            serde_json::json!({
+                "title": "Say \"hi\"",
+                "rating": null,
+                "meta": {"author": null},
+                "prices": [1.5, null],
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_omit_nulls_and_empty() {
+        let opts = SerializeOptions {
+            omit_nulls: true,
+            omit_empty: true,
+            ..Default::default()
+        };
+        let res = mixed_value().to_json_string_with(opts);
+        assert_eq!(
+            parse_json(&res),
+            serde_json::json!({
+                "title": "Say \"hi\"",
+                "prices": [1.5, null],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_json_value() {
+        let json = mixed_value().to_json_string_with(SerializeOptions::default());
+        assert_eq!(value_from_json(&json), Some(mixed_value()));
+        assert_eq!(
+            value_from_json(r#"[1, 1.0, 2e3, "x", true]"#),
+            Some(Value::Array(vec![
+                Value::Int(1),
+                Value::Float(1.0),
+                Value::Float(2000.0),
+                Value::from("x"),
+                Value::Bool(true),
+            ]))
+        );
+        assert_eq!(value_from_json("{invalid"), None);
+    }
+
+    #[test]
+    fn serialize_pretty() {
+        let val = Value::from_iter([(
+            "prices".to_string(),
+            Value::Array(vec![Value::Int(1), Value::Null]),
+        )]);
+        let opts = SerializeOptions {
+            omit_nulls: true,
+            omit_empty: true,
+            pretty: true,
+        };
+        let res = val.to_json_string_with(opts);
+        assert_eq!(res, "{\n  \"prices\": [\n    1,\n    null\n  ]\n}");
+    }
+}
@@ -1,4 +1,6 @@
-use dom_finder::{Config, Finder, Value};
+use std::time::{Duration, Instant};
+
+use dom_finder::{Config, ExtractError, Finder, ParseError, ValidationError, Value};
 use dom_query::Document;
 
 const CFG_YAML: &str = r"
@@ -217,3 +219,1751 @@ fn value_wrong_extraction_entity() {
     let none_val: Option<Value> = url_val.and_then(|v| v.from_path("nonsense"));
     assert!(none_val.is_none());
 }
+
+#[test]
+fn extract_as_tuple() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: rows
+      base_path: table tr.nutrition-item
+      many: true
+      as_tuple: true
+      children:
+        - name: name
+          base_path: td:nth-child(1)
+          extract: text
+        - name: calories
+          base_path: td:nth-child(2)
+          extract: text
+          pipeline: [ [regex_find, '^(\d+)'] ]
+          cast: int
+        - name: vitamin_c
+          base_path: td:nth-child(3)
+          extract: text
+  ";
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC_NUT);
+
+    let count: Option<i64> = res.from_path("root.rows.#").and_then(|v| v.into());
+    assert_eq!(count.unwrap(), 14);
+
+    let name: Option<String> = res.from_path("root.rows.0.0").and_then(|v| v.into());
+    let calories: Option<i64> = res.from_path("root.rows.0.1").and_then(|v| v.into());
+    let vitamin_c: Option<String> = res.from_path("root.rows.0.2").and_then(|v| v.into());
+    assert_eq!(
+        (name.unwrap(), calories.unwrap(), vitamin_c.unwrap()),
+        ("Apple".to_string(), 52, "10.3mg".to_string())
+    );
+}
+
+#[test]
+fn default_value_on_missing_field() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: title
+      base_path: h1
+      extract: text
+      pipeline: [ [normalize_spaces] ]
+    - name: rating
+      base_path: span.rating
+      extract: text
+      cast: int
+      default_value: '5'
+  ";
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC_NUT);
+    let rating: Option<i64> = res.from_path("root.rating").and_then(|v| v.into());
+    assert_eq!(rating.unwrap(), 5);
+}
+
+#[test]
+fn filter_nodes_by_attribute() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: urls
+      base_path: a[href]
+      many: true
+      extract: href
+      filter: [href, '^https://']
+  ";
+    let html = r#"<html><body>
+        <a href="https://example.com/1">1</a>
+        <a href="http://example.com/2">2</a>
+        <a href="/3">3</a>
+        <a href="https://example.com/4">4</a>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let urls: Option<Vec<String>> = res.from_path("root.urls").and_then(|v| v.into());
+    assert_eq!(
+        urls.unwrap(),
+        vec!["https://example.com/1", "https://example.com/4"]
+    );
+}
+
+#[test]
+fn parse_unwrapped() {
+    let finder: Finder = Config::from_yaml(CFG_YAML).unwrap().try_into().unwrap();
+    let unwrapped = finder.parse_unwrapped(HTML_DOC);
+    let wrapped = finder.parse(HTML_DOC).from_path("root");
+    assert_eq!(Some(unwrapped), wrapped);
+}
+
+#[test]
+fn skip_hidden_nodes() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: items
+      base_path: ul li
+      many: true
+      extract: text
+      skip_hidden: true
+    - name: first
+      base_path: ul li
+      extract: text
+      skip_hidden: true
+  ";
+    let html = r#"<html><body>
+        <ul>
+            <li style="display:none">Hidden</li>
+            <li>One</li>
+            <li style="Visibility: Hidden">Invisible</li>
+            <li>Two</li>
+        </ul>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let items: Option<Vec<String>> = res.from_path("root.items").and_then(|v| v.into());
+    assert_eq!(items.unwrap(), vec!["One", "Two"]);
+    let first: Option<String> = res.from_path("root.first").and_then(|v| v.into());
+    assert_eq!(first.unwrap(), "One");
+}
+
+#[test]
+fn select_by_role() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: buttons
+      base_path: body *
+      many: true
+      extract: text
+      role: button
+  ";
+    let html = r#"<html><body>
+        <button>Save</button>
+        <div role="button">Cancel</div>
+        <div>Plain</div>
+        <button role="tab">Tab</button>
+        <input type="submit" value="Send">
+        <a href="/x">Link</a>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let buttons: Option<Vec<String>> = res.from_path("root.buttons").and_then(|v| v.into());
+    assert_eq!(buttons.unwrap(), vec!["Save", "Cancel", ""]);
+}
+
+#[test]
+fn emit_null_fields() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: items
+      base_path: div.item
+      many: true
+      children:
+        - name: title
+          base_path: h2
+          extract: text
+        - name: price
+          base_path: span.price
+          extract: text
+          emit_null: true
+        - name: rating
+          base_path: span.rating
+          extract: text
+  ";
+    let html = r#"<html><body>
+        <div class="item"><h2>Apple</h2><span class="price">1.5</span></div>
+        <div class="item"><h2>Banana</h2></div>
+        <div class="item"><p>Nothing</p></div>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let items = res.from_path("root.items").unwrap();
+    let expected = Value::Array(vec![
+        Value::from_iter([
+            ("title".to_string(), Value::from("Apple")),
+            ("price".to_string(), Value::from("1.5")),
+        ]),
+        Value::from_iter([
+            ("title".to_string(), Value::from("Banana")),
+            ("price".to_string(), Value::Null),
+        ]),
+    ]);
+    assert_eq!(items, expected);
+}
+
+#[test]
+fn stable_id_from_fields() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: items
+      base_path: div.item
+      many: true
+      id_from: [url, title]
+      children:
+        - name: url
+          base_path: a
+          extract: href
+        - name: title
+          base_path: a
+          extract: text
+        - name: price
+          base_path: span
+          extract: text
+  ";
+    let html = r#"<html><body>
+        <div class="item"><a href="/a">Apple</a><span>1.5</span></div>
+        <div class="item"><a href="/a">Apple</a><span>2.0</span></div>
+        <div class="item"><a href="/b">Banana</a></div>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let ids: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("root.items.#._id")
+        .and_then(|v| v.into());
+    let ids = ids.unwrap();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids[0].len(), 8);
+    assert_eq!(ids[0], ids[1]);
+    assert_ne!(ids[0], ids[2]);
+
+    let again: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("root.items.#._id")
+        .and_then(|v| v.into());
+    assert_eq!(again.unwrap(), ids);
+}
+
+#[test]
+fn fragment_input_mode() {
+    let cfg_yaml: &str = r"
+  name: items
+  input_mode: fragment
+  base_path: html > ul > li
+  many: true
+  extract: text
+  ";
+    let html = "<ul><li>One</li><li>Two</li></ul>";
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let items: Option<Vec<String>> = finder.parse(html).from_path("items").and_then(|v| v.into());
+    assert_eq!(items.unwrap(), vec!["One", "Two"]);
+
+    // the same selector does not match in the document mode, because the list is wrapped into `<body>`
+    let cfg_yaml = cfg_yaml.replace("input_mode: fragment", "input_mode: document");
+    let finder: Finder = Config::from_yaml(&cfg_yaml).unwrap().try_into().unwrap();
+    assert_eq!(finder.parse(html).from_path("items"), Some(Value::Null));
+}
+
+#[test]
+fn trim_extracted_html() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: trimmed
+      base_path: div
+      extract: inner_html
+      trim_html: true
+    - name: raw
+      base_path: div
+      extract: inner_html
+  ";
+    let html = "<html><body><div>\n    <p>One</p>\n    <p>Two</p>\n</div></body></html>";
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let trimmed: Option<String> = res.from_path("root.trimmed").and_then(|v| v.into());
+    assert_eq!(trimmed.unwrap(), "<p>One</p>\n    <p>Two</p>");
+    let raw: Option<String> = res.from_path("root.raw").and_then(|v| v.into());
+    assert_eq!(raw.unwrap(), "\n    <p>One</p>\n    <p>Two</p>\n");
+}
+
+#[test]
+fn split_base_path() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: links
+      base_path: .footer a, .header a, a.home
+      split_path: true
+      many: true
+      extract: href
+    - name: first
+      base_path: .footer a, .header a
+      split_path: true
+      extract: href
+  ";
+    let html = r#"<html><body>
+        <div class="header"><a class="home" href="/">Home</a><a href="/about">About</a></div>
+        <div class="content"><a href="/skip">Skip</a></div>
+        <div class="footer"><a href="/terms">Terms</a></div>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let links: Option<Vec<String>> = res.from_path("root.links").and_then(|v| v.into());
+    assert_eq!(links.unwrap(), vec!["/terms", "/", "/about"]);
+    let first: Option<String> = res.from_path("root.first").and_then(|v| v.into());
+    assert_eq!(first.unwrap(), "/terms");
+}
+
+#[test]
+fn split_base_path_invalid_fragment() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: .a, .b[
+  split_path: true
+  many: true
+  extract: href
+  ";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let res = Finder::new(&cfg);
+    assert!(matches!(res, Err(ParseError::InvalidSelector(ref s)) if s == ".b["));
+}
+
+#[test]
+fn case_insensitive_class_fallback() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: strict
+      base_path: .Result
+      many: true
+      extract: text
+    - name: relaxed
+      base_path: .Result
+      many: true
+      extract: text
+      ci_class: true
+    - name: exact
+      base_path: .Ad, .result
+      many: true
+      extract: text
+      ci_class: true
+  ";
+    let html = r#"<html><body>
+        <div class="result">One</div>
+        <div class="RESULT">Two</div>
+        <div class="ad">Ad</div>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    assert_eq!(res.from_path("root.strict"), None);
+    let relaxed: Option<Vec<String>> = res.from_path("root.relaxed").and_then(|v| v.into());
+    assert_eq!(relaxed.unwrap(), vec!["One", "Two"]);
+    // the fallback is not applied, because the original path matches
+    let exact: Option<Vec<String>> = res.from_path("root.exact").and_then(|v| v.into());
+    assert_eq!(exact.unwrap(), vec!["One"]);
+}
+
+#[test]
+fn limit_many_results() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      limit: 5
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+  ";
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC);
+    let urls: Option<Vec<String>> = res.from_path("root.results.#.url").and_then(|v| v.into());
+    let urls = urls.unwrap();
+    assert_eq!(urls.len(), 5);
+
+    let unlimited_cfg = Config::from_yaml(&cfg_yaml.replace("limit: 5", "")).unwrap();
+    let unlimited: Finder = unlimited_cfg.try_into().unwrap();
+    let all_urls: Option<Vec<String>> = unlimited
+        .parse(HTML_DOC)
+        .from_path("root.results.#.url")
+        .and_then(|v| v.into());
+    assert_eq!(urls, all_urls.unwrap()[..5]);
+}
+
+#[test]
+fn skip_many_results() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      enumerate: true
+      skip: 3
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+  ";
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC);
+    let urls: Option<Vec<String>> = res.from_path("root.results.#.url").and_then(|v| v.into());
+    let indices: Option<Vec<i64>> = res.from_path("root.results.#.index").and_then(|v| v.into());
+
+    let all_cfg = Config::from_yaml(&cfg_yaml.replace("skip: 3", "")).unwrap();
+    let all: Finder = all_cfg.try_into().unwrap();
+    let all_urls: Option<Vec<String>> = all
+        .parse(HTML_DOC)
+        .from_path("root.results.#.url")
+        .and_then(|v| v.into());
+    let all_urls = all_urls.unwrap();
+    assert_eq!(urls.unwrap(), all_urls[3..]);
+    let indices = indices.unwrap();
+    assert_eq!(indices[0], 0);
+    assert_eq!(indices.len(), all_urls.len() - 3);
+}
+
+#[test]
+fn reverse_many_results() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      enumerate: true
+      reverse: true
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+    - name: urls
+      base_path: div.serp__results div.result h2.result__title > a[href]
+      many: true
+      reverse: true
+      extract: href
+  ";
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC);
+    let urls: Option<Vec<String>> = res.from_path("root.results.#.url").and_then(|v| v.into());
+    let urls = urls.unwrap();
+    let indices: Option<Vec<i64>> = res.from_path("root.results.#.index").and_then(|v| v.into());
+    assert_eq!(indices.unwrap(), (0..urls.len() as i64).collect::<Vec<_>>());
+
+    let direct_cfg = Config::from_yaml(&cfg_yaml.replace("reverse: true", "")).unwrap();
+    let direct: Finder = direct_cfg.try_into().unwrap();
+    let direct_urls: Option<Vec<String>> = direct
+        .parse(HTML_DOC)
+        .from_path("root.results.#.url")
+        .and_then(|v| v.into());
+    let mut direct_urls = direct_urls.unwrap();
+    direct_urls.reverse();
+    assert_eq!(urls, direct_urls);
+
+    let scalar_urls: Option<Vec<String>> = res.from_path("root.urls").and_then(|v| v.into());
+    assert_eq!(scalar_urls.unwrap(), direct_urls);
+
+    let field_urls: Option<Vec<String>> = finder.parse_field(HTML_DOC, "results.url").into();
+    assert_eq!(field_urls.unwrap(), direct_urls);
+
+    for name in ["results", "urls"] {
+        let mut items = vec![];
+        finder.parse_each(HTML_DOC, name, |v| items.push(v));
+        assert_eq!(
+            Some(Value::Array(items)),
+            res.from_path(&format!("root.{}", name))
+        );
+    }
+}
+
+#[test]
+fn unique_many_results() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: links
+      base_path: div.serp__results a[href]
+      many: true
+      unique: true
+      extract: href
+    - name: link_objects
+      base_path: div.serp__results a[href]
+      many: true
+      unique: true
+      children:
+        - name: url
+          inherit: true
+          extract: href
+  ";
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC);
+    let links: Option<Vec<String>> = res.from_path("root.links").and_then(|v| v.into());
+    let links = links.unwrap();
+
+    let direct_cfg = Config::from_yaml(&cfg_yaml.replace("unique: true", "")).unwrap();
+    let direct: Finder = direct_cfg.try_into().unwrap();
+    let all_links: Option<Vec<String>> = direct
+        .parse(HTML_DOC)
+        .from_path("root.links")
+        .and_then(|v| v.into());
+    let all_links = all_links.unwrap();
+    assert!(links.len() < all_links.len());
+
+    let mut expected: Vec<String> = vec![];
+    for link in all_links {
+        if !expected.contains(&link) {
+            expected.push(link);
+        }
+    }
+    assert_eq!(links, expected);
+
+    let object_urls: Option<Vec<String>> = res
+        .from_path("root.link_objects.#.url")
+        .and_then(|v| v.into());
+    assert_eq!(object_urls.unwrap(), expected);
+
+    for name in ["links", "link_objects"] {
+        let mut items = vec![];
+        finder.parse_each(HTML_DOC, name, |v| items.push(v));
+        assert_eq!(
+            Some(Value::Array(items)),
+            res.from_path(&format!("root.{}", name))
+        );
+    }
+
+    let reversed_cfg =
+        Config::from_yaml(&cfg_yaml.replace("unique: true", "unique: true\n      reverse: true"))
+            .unwrap();
+    let reversed: Finder = reversed_cfg.try_into().unwrap();
+    let reversed_res = reversed.parse(HTML_DOC);
+    let mut reversed_links: Vec<Value> = vec![];
+    reversed.parse_each(HTML_DOC, "links", |v| reversed_links.push(v));
+    assert_eq!(
+        Some(Value::Array(reversed_links)),
+        reversed_res.from_path("root.links")
+    );
+    expected.reverse();
+    let reversed_urls: Option<Vec<String>> =
+        reversed_res.from_path("root.links").and_then(|v| v.into());
+    assert_eq!(reversed_urls.unwrap(), expected);
+}
+
+#[test]
+fn try_parse_required_fields() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      required: true
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+          required: true
+        - name: rating
+          base_path: span.rating
+          extract: text
+          required: true
+    - name: author
+      base_path: span.author
+      extract: text
+      required: true
+    - name: optional
+      base_path: span.optional
+      extract: text
+  ";
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.try_parse(HTML_DOC);
+    let Err(ExtractError::MissingRequired(paths)) = res else {
+        panic!("expected missing required fields");
+    };
+    assert_eq!(paths, vec!["root.results.rating", "root.author"]);
+
+    let cfg_yaml = cfg_yaml
+        .replace("span.rating", "a.result__url")
+        .replace("span.author", "h2");
+    let finder: Finder = Config::from_yaml(&cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.try_parse(HTML_DOC).unwrap();
+    assert_eq!(res, finder.parse(HTML_DOC));
+}
+
+#[test]
+fn parse_with_match_counts() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+        - name: rating
+          base_path: span.rating
+          extract: text
+    - name: missing
+      base_path: div.missing
+      children:
+        - name: title
+          base_path: h2
+          extract: text
+  ";
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let (res, counts) = finder.parse_with_counts(HTML_DOC);
+    assert_eq!(res, finder.parse(HTML_DOC));
+
+    let Value::Object(counts) = counts else {
+        panic!("counts must be an object");
+    };
+    let expected = [
+        ("root", 1),
+        ("root.results", 21),
+        ("root.results.url", 21),
+        ("root.results.rating", 0),
+        ("root.missing", 0),
+        ("root.missing.title", 0),
+    ];
+    let got: Vec<(&str, Value)> = counts
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.clone()))
+        .collect();
+    let expected: Vec<(&str, Value)> = expected
+        .into_iter()
+        .map(|(k, v)| (k, Value::Int(v)))
+        .collect();
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn section_heading_context() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: paragraphs
+      base_path: article p
+      many: true
+      section_heading: h2, h3
+      children:
+        - name: text
+          inherit: true
+          extract: text
+  ";
+    let html = r#"<html><body><article>
+    <p>Intro</p>
+    <h2>
+        Getting started
+    </h2>
+    <p>Install</p>
+    <div><h3>Usage</h3><p>Run</p></div>
+    <p>Configure</p>
+    <h2>FAQ</h2>
+    <section><p>Why?</p></section>
+    </article></body></html>"#;
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let texts: Option<Vec<String>> = res
+        .from_path("root.paragraphs.#.text")
+        .and_then(|v| v.into());
+    assert_eq!(
+        texts.unwrap(),
+        vec!["Intro", "Install", "Run", "Configure", "Why?"]
+    );
+    assert!(res.from_path("root.paragraphs.0._section").is_none());
+    let sections: Option<Vec<String>> = res
+        .from_path("root.paragraphs.#._section")
+        .and_then(|v| v.into());
+    assert_eq!(
+        sections.unwrap(),
+        vec!["Getting started", "Usage", "Usage", "FAQ"]
+    );
+
+    let cfg_yaml = cfg_yaml.replace("h2, h3", "h2[");
+    let res = Finder::new(&Config::from_yaml(&cfg_yaml).unwrap());
+    assert!(matches!(res, Err(ParseError::InvalidSelector(_))));
+}
+
+#[test]
+fn variants_by_item_kind() {
+    let cfg_yaml: &str = r#"
+  name: root
+  base_path: html
+  children:
+    - name: items
+      base_path: div.feed > div.item
+      many: true
+      children:
+        - name: title
+          base_path: h3
+          extract: text
+      variants:
+        - - '[data-kind="video"]'
+          - - name: title
+              base_path: h3
+              extract: text
+            - name: duration
+              base_path: span.duration
+              extract: text
+        - - '[data-kind="article"]'
+          - - name: title
+              base_path: h3
+              extract: text
+            - name: author
+              base_path: span.author
+              extract: text
+            - name: words
+              base_path: span.words
+              extract: text
+              cast: int
+  "#;
+    let html = r#"<html><body><div class="feed">
+    <div class="item" data-kind="video"><h3>Clip</h3><span class="duration">3:15</span></div>
+    <div class="item" data-kind="article"><h3>Story</h3><span class="author">Ann</span><span class="words">900</span></div>
+    <div class="item" data-kind="ad"><h3>Promo</h3><span class="duration">0:30</span></div>
+    </div></body></html>"#;
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+
+    let video = Value::from_iter([
+        ("title".to_string(), Value::from("Clip")),
+        ("duration".to_string(), Value::from("3:15")),
+    ]);
+    let article = Value::from_iter([
+        ("title".to_string(), Value::from("Story")),
+        ("author".to_string(), Value::from("Ann")),
+        ("words".to_string(), Value::Int(900)),
+    ]);
+    let other = Value::from_iter([("title".to_string(), Value::from("Promo"))]);
+    assert_eq!(
+        res.from_path("root.items"),
+        Some(Value::Array(vec![video, article, other]))
+    );
+
+    let durations: Option<Vec<String>> = finder.parse_field(html, "items.duration").into();
+    assert_eq!(durations.unwrap(), vec!["3:15"]);
+    let words: Option<Vec<i64>> = finder.parse_field(html, "items.words").into();
+    assert_eq!(words.unwrap(), vec![900]);
+
+    let effective: Finder = finder.effective_config().try_into().unwrap();
+    assert_eq!(effective.parse(html), res);
+}
+
+#[test]
+fn extract_count() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results_count
+      base_path: div.serp__results div.result
+      many: true
+      extract: count
+    - name: missing_count
+      base_path: div.no-such-class
+      many: true
+      extract: count
+  ";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(HTML_DOC);
+    assert_eq!(res.from_path("root.results_count"), Some(Value::Int(21)));
+    assert_eq!(res.from_path("root.missing_count"), Some(Value::Int(0)));
+
+    let mut counts = vec![];
+    finder.parse_each(HTML_DOC, "results_count", |v| counts.push(v));
+    assert_eq!(counts, vec![Value::Int(21)]);
+    let mut counts = vec![];
+    finder.parse_each(HTML_DOC, "missing_count", |v| counts.push(v));
+    assert_eq!(counts, vec![Value::Int(0)]);
+
+    let single_yaml: &str = r"
+  name: root
+  base_path: div.result
+  extract: count
+  ";
+    assert!(matches!(
+        Config::from_yaml(single_yaml).unwrap().validate(),
+        Err(ValidationError::CountRequiresMany)
+    ));
+}
+
+#[test]
+fn rename_keys() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      rename_keys:
+        url: link
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+        - name: title
+          base_path: h2.result__title
+          extract: text
+  ";
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC);
+    let links: Option<Vec<String>> = res.from_path("root.results.#.link").and_then(|v| v.into());
+    assert_eq!(links.unwrap().len(), 21);
+    let urls: Option<Vec<String>> = res.from_path("root.results.#.url").and_then(|v| v.into());
+    assert!(urls.unwrap().is_empty());
+}
+
+#[test]
+fn rename_keys_swap_and_chain() {
+    let cfg_yaml: &str = r#"
+  name: root
+  base_path: html
+  children:
+    - name: swapped
+      base_path: li
+      many: true
+      rename_keys:
+        a: b
+        b: a
+      children:
+        - name: a
+          base_path: a
+          extract: href
+        - name: b
+          base_path: a
+          extract: text
+    - name: chained
+      base_path: li
+      many: true
+      rename_keys:
+        a: b
+        b: c
+      children:
+        - name: a
+          base_path: a
+          extract: href
+        - name: b
+          base_path: a
+          extract: text
+    - name: collided
+      base_path: li
+      many: true
+      rename_keys:
+        a: b
+      children:
+        - name: b
+          base_path: a
+          extract: text
+        - name: a
+          base_path: a
+          extract: href
+  "#;
+    let html = r#"<html><body><ul><li><a href="/1">One</a></li></ul></body></html>"#;
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+
+    let swapped = Value::from_iter([
+        ("b".to_string(), Value::from("/1")),
+        ("a".to_string(), Value::from("One")),
+    ]);
+    assert_eq!(
+        res.from_path("root.swapped"),
+        Some(Value::Array(vec![swapped]))
+    );
+    let chained = Value::from_iter([
+        ("b".to_string(), Value::from("/1")),
+        ("c".to_string(), Value::from("One")),
+    ]);
+    assert_eq!(
+        res.from_path("root.chained"),
+        Some(Value::Array(vec![chained]))
+    );
+    let collided = Value::from_iter([("b".to_string(), Value::from("/1"))]);
+    assert_eq!(
+        res.from_path("root.collided"),
+        Some(Value::Array(vec![collided]))
+    );
+}
+
+#[test]
+fn extract_best_srcset_candidate() {
+    let cfg_yaml: &str = r"
+  name: image
+  base_path: img[srcset]
+  extract: srcset
+  pipeline: [ [srcset, best] ]
+  ";
+    let html = r#"<html><body><img src="a.jpg" srcset="a.jpg 1x, b.jpg 2x"></body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let url: Option<String> = res.from_path("image").and_then(|v| v.into());
+    assert_eq!(url.unwrap(), "b.jpg");
+}
+
+#[test]
+fn parse_with_deadline() {
+    let finder: Finder = Config::from_yaml(CFG_YAML).unwrap().try_into().unwrap();
+
+    let deadline = Instant::now();
+    let res = finder.parse_with_deadline(HTML_DOC, deadline);
+    assert!(matches!(res, Err(ParseError::Timeout)));
+
+    let deadline = Instant::now() + Duration::from_secs(60);
+    let res = finder.parse_with_deadline(HTML_DOC, deadline).unwrap();
+    assert_eq!(res, finder.parse(HTML_DOC));
+}
+
+#[test]
+fn extract_json_from_comment() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: state_id
+      base_path: div#state
+      extract: comment
+      pipeline: [ [extract_json, 'item.id'] ]
+      cast: int
+  ";
+    let html = r#"<html><body>
+        <div id="state"><!-- {"item": {"id": 42, "name": "Apple"}} --><p>Visible text</p></div>
+    </body></html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+    let id: Option<i64> = res.from_path("root.state_id").and_then(|v| v.into());
+    assert_eq!(id.unwrap(), 42);
+}
+
+#[test]
+fn flatten_preserves_keys_order() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: first
+      base_path: table tr.nutrition-item
+      children:
+        - name: name
+          base_path: td:nth-child(1)
+          extract: text
+        - name: details
+          inherit: true
+          flatten: true
+          children:
+            - name: vitamin_c
+              base_path: td:nth-child(3)
+              extract: text
+            - name: calories
+              base_path: td:nth-child(2)
+              extract: text
+            - name: sugar
+              base_path: td:nth-child(4)
+              extract: text
+        - name: fiber
+          inherit: true
+          extract: data-nutrition
+          pipeline: [ [extract_json, 'fiber' ] ]
+  ";
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC_NUT);
+    let Some(Value::Object(first)) = res.from_path("root.first") else {
+        panic!("expected an object");
+    };
+    let keys: Vec<&str> = first.keys().map(|k| k.as_str()).collect();
+    assert_eq!(
+        keys,
+        vec!["name", "vitamin_c", "calories", "sugar", "fiber"]
+    );
+}
+
+#[test]
+fn extract_table_rows() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: nutrition
+      base_path: table
+      table: true
+  ";
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(HTML_DOC_NUT);
+
+    let count: Option<i64> = res.from_path("root.nutrition.#").and_then(|v| v.into());
+    assert_eq!(count.unwrap(), 14);
+
+    let Some(Value::Object(first)) = res.from_path("root.nutrition.0") else {
+        panic!("expected an object");
+    };
+    let keys: Vec<&str> = first.keys().map(|k| k.as_str()).collect();
+    assert_eq!(keys, vec!["Fruit", "Calories", "Vitamin C", "Sugar"]);
+
+    let fruit: Option<String> = first.get("Fruit").and_then(|v| v.into());
+    let vitamin_c: Option<String> = first.get("Vitamin C").and_then(|v| v.into());
+    assert_eq!(fruit.unwrap(), "Apple");
+    assert_eq!(vitamin_c.unwrap(), "10.3mg");
+}
+
+#[test]
+fn extract_table_rows_with_colspan() {
+    let cfg_yaml: &str = r"
+  name: rows
+  base_path: table
+  table: true
+  ";
+    let html = r#"<table>
+        <tr><th>Name</th><th colspan="2">Price</th></tr>
+        <tr><td>Apple</td><td>1.5</td><td>USD</td></tr>
+        <tr><td colspan="2">Total</td><td>3</td></tr>
+    </table>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+
+    let price: Option<String> = res.from_path("rows.0.Price").and_then(|v| v.into());
+    assert_eq!(price.unwrap(), "1.5");
+    let name: Option<String> = res.from_path("rows.1.Name").and_then(|v| v.into());
+    assert_eq!(name.unwrap(), "Total");
+    let price: Option<String> = res.from_path("rows.1.Price").and_then(|v| v.into());
+    assert_eq!(price.unwrap(), "3");
+}
+
+#[test]
+fn extract_page_fields() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: title
+      page: title
+    - name: canonical
+      page: canonical
+    - name: description
+      page: description
+    - name: favicon
+      page: favicon
+  ";
+    let html = r#"<html>
+    <head>
+        <title>Fruit Nutrition Facts</title>
+        <link rel="canonical" href="https://example.com/fruits">
+        <meta name="description" content="A brief list of fruit nutrition facts">
+        <link rel="shortcut icon" href="/favicon.ico">
+    </head>
+    <body><title>Not a page title</title></body>
+    </html>"#;
+
+    let finder: Finder = Config::from_yaml(cfg_yaml).unwrap().try_into().unwrap();
+    let res = finder.parse(html);
+
+    let title: Option<String> = res.from_path("root.title").and_then(|v| v.into());
+    assert_eq!(title.unwrap(), "Fruit Nutrition Facts");
+    let canonical: Option<String> = res.from_path("root.canonical").and_then(|v| v.into());
+    assert_eq!(canonical.unwrap(), "https://example.com/fruits");
+    let description: Option<String> = res.from_path("root.description").and_then(|v| v.into());
+    assert_eq!(
+        description.unwrap(),
+        "A brief list of fruit nutrition facts"
+    );
+    let favicon: Option<String> = res.from_path("root.favicon").and_then(|v| v.into());
+    assert_eq!(favicon.unwrap(), "/favicon.ico");
+}
+
+#[test]
+fn keep_results_where() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.result
+      many: true
+      enumerate: true
+      keep_where: [nofollow, 'false']
+      children:
+        - name: url
+          base_path: a[href]
+          extract: href
+        - name: nofollow
+          base_path: a[href]
+          extract: rel
+          pipeline: [ [ regex_find, nofollow ] ]
+          cast: bool
+  ";
+    let html = r#"<html><body>
+        <div class="result"><a href="/1" rel="nofollow">One</a></div>
+        <div class="result"><a href="/2" rel="noopener">Two</a></div>
+        <div class="result"><a href="/3" rel="nofollow noopener">Three</a></div>
+        <div class="result"><a href="/4" rel="external">Four</a></div>
+    </body></html>"#;
+
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let urls: Option<Vec<String>> = res.from_path("root.results.#.url").and_then(|v| v.into());
+    assert_eq!(urls.unwrap(), vec!["/2", "/4"]);
+    let urls: Option<Vec<String>> = finder.parse_field(html, "results.url").into();
+    assert_eq!(urls.unwrap(), vec!["/2", "/4"]);
+    let indexes: Option<Vec<i64>> = res.from_path("root.results.#.index").and_then(|v| v.into());
+    assert_eq!(indexes.unwrap(), vec![0, 1]);
+
+    let mut cfg = Config::from_yaml(cfg_yaml).unwrap();
+    cfg.children[0].keep_where = Some(("url".to_string(), "!=/2".to_string()));
+    let res = Finder::new(&cfg).unwrap().parse(html);
+    let urls: Option<Vec<String>> = res.from_path("root.results.#.url").and_then(|v| v.into());
+    assert_eq!(urls.unwrap(), vec!["/1", "/3", "/4"]);
+}
+
+#[test]
+fn extract_accessible_name() {
+    let cfg_yaml: &str = r"
+  name: buttons
+  base_path: button
+  many: true
+  extract: accessible_name
+  ";
+    let html = r#"<html><body>
+    <span id="cart-label">Shopping   cart</span>
+    <button aria-label="Close dialog">X</button>
+    <button aria-labelledby="cart-label missing">&#128722;</button>
+    <button title="Search">&#128269;</button>
+    <button aria-label=" ">
+        Submit   order
+    </button>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let names: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("buttons")
+        .and_then(|v| v.into());
+    assert_eq!(
+        names.unwrap(),
+        vec!["Close dialog", "Shopping cart", "Search", "Submit order"]
+    );
+}
+
+#[test]
+fn stop_on_empty() {
+    let cfg_yaml: &str = r"
+  name: items
+  base_path: ul li
+  many: true
+  stop_on_empty: true
+  children:
+    - name: title
+      base_path: span.title
+      extract: text
+  ";
+    let html = r#"<html><body><ul>
+    <li><span class="title">One</span></li>
+    <li><span class="title">Two</span></li>
+    <li class="placeholder"></li>
+    <li><span class="title">Three</span></li>
+    <li class="placeholder"></li>
+    </ul></body></html>"#;
+
+    let mut cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let titles: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("items.#.title")
+        .and_then(|v| v.into());
+    assert_eq!(titles.unwrap(), vec!["One", "Two"]);
+
+    cfg.stop_on_empty = false;
+    let finder = Finder::new(&cfg).unwrap();
+    let titles: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("items.#.title")
+        .and_then(|v| v.into());
+    assert_eq!(titles.unwrap(), vec!["One", "Two", "Three"]);
+}
+
+#[test]
+fn effective_config() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: title
+      page: title
+      pipeline: [ [ normalize_spaces ], [ slugify ] ]
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      keep_where: [ label, '!=Official site' ]
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+        - name: label
+          base_path: .label
+          extract: text
+          pipeline: [ [ trim_space ] ]
+  ";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let effective = finder.effective_config();
+
+    let title = &effective.children[0];
+    assert_eq!(title.page, None);
+    assert_eq!(title.base_path, "head title");
+    assert_eq!(title.extract, "text");
+    assert_eq!(
+        title.pipeline,
+        vec![
+            vec!["normalize_spaces".to_string()],
+            vec!["slugify".to_string()]
+        ]
+    );
+    assert_eq!(
+        effective.children[1].keep_where,
+        Some(("label".to_string(), "!=Official site".to_string()))
+    );
+
+    let effective_finder = Finder::new(&effective).unwrap();
+    assert_eq!(effective_finder.parse(HTML_DOC), finder.parse(HTML_DOC));
+}
+
+#[test]
+fn group_flat_siblings() {
+    let cfg_yaml: &str = r"
+  name: specs
+  base_path: dl.specs > dt, dl.specs > dd
+  many: true
+  group_by: 2
+  children:
+    - name: term
+      inherit: true
+      extract: text
+    - name: value
+      inherit: true
+      extract: text
+  ";
+    let html = r#"<html><body>
+    <dl class="specs">
+        <dt>Weight</dt><dd>182 g</dd>
+        <dt>Color</dt><dd>Red</dd>
+        <dt>Origin</dt>
+    </dl>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let terms: Option<Vec<String>> = res.from_path("specs.#.term").and_then(|v| v.into());
+    assert_eq!(terms.unwrap(), vec!["Weight", "Color", "Origin"]);
+    let values: Option<Vec<String>> = res.from_path("specs.#.value").and_then(|v| v.into());
+    assert_eq!(values.unwrap(), vec!["182 g", "Red"]);
+
+    let values: Option<Vec<String>> = finder.parse_field(html, "value").into();
+    assert_eq!(values.unwrap(), vec!["182 g", "Red"]);
+    let terms: Option<Vec<String>> = finder.parse_field(html, "term").into();
+    assert_eq!(terms.unwrap(), vec!["Weight", "Color", "Origin"]);
+}
+
+#[test]
+fn parse_limited() {
+    let cfg = Config::from_yaml(CFG_YAML).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let res = finder.parse_limited(HTML_DOC, 1024);
+    assert!(matches!(res, Err(ParseError::InputTooLarge(len, 1024)) if len == HTML_DOC.len()));
+
+    let res = finder.parse_limited(HTML_DOC, HTML_DOC.len()).unwrap();
+    assert_eq!(res, finder.parse(HTML_DOC));
+}
+
+#[test]
+fn extract_sibling_index() {
+    let cfg_yaml: &str = r"
+  name: items
+  base_path: ul > li.item
+  many: true
+  children:
+    - name: title
+      inherit: true
+      extract: text
+    - name: index
+      inherit: true
+      extract: sibling_index
+      cast: int
+  ";
+    let html = r#"<html><body><ul>
+    <li class="item">One</li>
+    <!-- comment -->
+    <li class="ad">Ad</li>
+    <li class="item">Three</li>
+    </ul></body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let title: Option<String> = res.from_path("items.1.title").and_then(|v| v.into());
+    assert_eq!(title.unwrap(), "Three");
+    let index: Option<i64> = res.from_path("items.1.index").and_then(|v| v.into());
+    assert_eq!(index.unwrap(), 2);
+}
+
+#[test]
+fn extract_tag_name() {
+    let cfg_yaml: &str = r"
+  name: items
+  base_path: div.feed > .entry
+  many: true
+  children:
+    - name: tag
+      inherit: true
+      extract: tag_name
+    - name: link_tag
+      base_path: a[href]
+      extract: tag_name
+    - name: attr
+      inherit: true
+      extract: attr:tag_name
+  ";
+    let html = r#"<html><body><div class="feed">
+    <article class="entry" tag_name="custom"><a href="/1">One</a></article>
+    <DIV class="entry"><a href="/2">Two</a></DIV>
+    </div></body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let tags: Option<Vec<String>> = res.from_path("items.#.tag").and_then(|v| v.into());
+    assert_eq!(tags.unwrap(), vec!["article", "div"]);
+    let link_tags: Option<Vec<String>> = res.from_path("items.#.link_tag").and_then(|v| v.into());
+    assert_eq!(link_tags.unwrap(), vec!["a", "a"]);
+    let attr: Option<String> = res.from_path("items.0.attr").and_then(|v| v.into());
+    assert_eq!(attr.unwrap(), "custom");
+}
+
+#[test]
+fn final_pipeline_on_result() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  final_pipeline: [ [replace, 'http://', 'https://'] ]
+  children:
+    - name: links
+      base_path: a[href]
+      many: true
+      extract: href
+    - name: title
+      base_path: h1
+      extract: text
+  ";
+    let html = r#"<html><body><h1>Read "http://" docs</h1>
+    <a href="http://example.com/1">1</a><a href="https://example.com/2">2</a>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let links: Option<Vec<String>> = res.from_path("root.links").and_then(|v| v.into());
+    assert_eq!(
+        links.unwrap(),
+        vec!["https://example.com/1", "https://example.com/2"]
+    );
+    let title: Option<String> = res.from_path("root.title").and_then(|v| v.into());
+    assert_eq!(title.unwrap(), r#"Read "https://" docs"#);
+
+    let cfg_yaml = cfg_yaml.replace("'http://', 'https://'", "'{', '('");
+    let finder = Finder::new(&Config::from_yaml(&cfg_yaml).unwrap()).unwrap();
+    assert_eq!(finder.parse(html), Value::Null);
+
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: title
+      base_path: h1
+      extract: text
+      final_pipeline: [ [trim_space] ]
+  ";
+    let res = Finder::new(&Config::from_yaml(cfg_yaml).unwrap());
+    assert!(matches!(
+        res,
+        Err(ParseError::Validation(
+            ValidationError::FinalPipelineNotRoot
+        ))
+    ));
+}
+
+#[test]
+fn extract_all_attrs() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: link
+      base_path: a
+      extract: attrs
+    - name: all
+      base_path: a, span
+      many: true
+      extract: attrs
+  ";
+    let html = r#"<html><body>
+    <a href="x" rel="nofollow">X</a><span>No attributes</span>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let expected = Value::from_iter([
+        ("href".to_string(), Value::from("x")),
+        ("rel".to_string(), Value::from("nofollow")),
+    ]);
+    assert_eq!(res.from_path("root.link"), Some(expected.clone()));
+    assert_eq!(
+        res.from_path("root.all"),
+        Some(Value::Array(vec![
+            expected,
+            Value::Object(Default::default())
+        ]))
+    );
+
+    let cfg_yaml = cfg_yaml.replace(
+        "extract: attrs\n    - name: all",
+        "extract: attrs\n      cast: int\n    - name: all",
+    );
+    let res = Finder::new(&Config::from_yaml(&cfg_yaml).unwrap());
+    assert!(matches!(res, Err(ParseError::IncompatibleCast(..))));
+}
+
+#[test]
+fn structured_extract_with_pipeline() {
+    for extract in ["attrs", "data", "class_list", "rel[]", "count"] {
+        let cfg_yaml = format!(
+            r"
+  name: root
+  base_path: a
+  many: true
+  extract: {}
+  pipeline: [ [trim_space] ]
+  ",
+            extract
+        );
+        let res = Finder::new(&Config::from_yaml(&cfg_yaml).unwrap());
+        assert!(
+            matches!(res, Err(ParseError::IncompatiblePipeline(..))),
+            "{}",
+            extract
+        );
+    }
+}
+
+#[test]
+fn extract_data_attrs() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: product
+      base_path: div.product
+      extract: data
+    - name: object_data
+      base_path: object
+      extract: attr:data
+  ";
+    let html = r#"<html><body>
+    <div class="product" id="p5" data-id="5" data-price="9.99" data-in-stock="">Apple</div>
+    <object data="movie.swf"></object>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let expected = Value::from_iter([
+        ("id".to_string(), Value::from("5")),
+        ("price".to_string(), Value::from("9.99")),
+        ("in-stock".to_string(), Value::from("")),
+    ]);
+    assert_eq!(res.from_path("root.product"), Some(expected));
+    let object_data: Option<String> = res.from_path("root.object_data").and_then(|v| v.into());
+    assert_eq!(object_data.unwrap(), "movie.swf");
+}
+
+#[test]
+fn extract_class_list() {
+    let cfg_yaml: &str = r"
+  name: items
+  base_path: div
+  many: true
+  extract: class_list
+  ";
+    let html = r#"<html><body><div class="a b  c"></div><div></div></body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let classes: Option<Vec<String>> = res.from_path("items.0").and_then(|v| v.into());
+    assert_eq!(classes.unwrap(), vec!["a", "b", "c"]);
+    assert_eq!(res.from_path("items.1"), Some(Value::Array(vec![])));
+}
+
+#[test]
+fn extract_attr_tokens() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: classes
+      base_path: div
+      extract: class[]
+    - name: rel
+      base_path: a
+      many: true
+      extract: attr:rel[]
+  ";
+    let html = r#"<html><body>
+    <div class=" a b  c "></div>
+    <a href="/1" rel="nofollow noopener">1</a><a href="/2">2</a>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let classes: Option<Vec<String>> = res.from_path("root.classes").and_then(|v| v.into());
+    assert_eq!(classes.unwrap(), vec!["a", "b", "c"]);
+    let rel: Option<Vec<String>> = res.from_path("root.rel.0").and_then(|v| v.into());
+    assert_eq!(rel.unwrap(), vec!["nofollow", "noopener"]);
+    assert_eq!(res.from_path("root.rel.#"), Some(Value::Int(1)));
+}
+
+#[test]
+fn post_pipeline_on_object() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: encoded
+      base_path: div.product
+      post_pipeline: [ [ trim_space ] ]
+      children:
+        - name: title
+          base_path: h2
+          extract: text
+        - name: price
+          base_path: span.price
+          extract: text
+          cast: float
+    - name: products
+      base_path: div.product
+      many: true
+      post_pipeline: [ [ replace, 'Apple', 'Green Apple' ] ]
+      post_pipeline_json: true
+      children:
+        - name: title
+          base_path: h2
+          extract: text
+  ";
+    let html = r#"<html><body>
+    <div class="product"><h2>Apple</h2><span class="price">1.5</span></div>
+    <div class="product"><h2>Banana</h2></div>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let encoded: Option<String> = res.from_path("root.encoded").and_then(|v| v.into());
+    assert_eq!(encoded.unwrap(), r#"{"title":"Apple","price":1.5}"#);
+
+    let titles: Option<Vec<String>> = res
+        .from_path("root.products.#.title")
+        .and_then(|v| v.into());
+    assert_eq!(titles.unwrap(), vec!["Green Apple", "Banana"]);
+}
+
+#[test]
+fn extract_reserved_attribute() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: html_attr
+      base_path: div[html]
+      extract: attr:html
+    - name: html
+      base_path: div[html]
+      extract: html
+  ";
+    let html = r#"<html><body><div html="attribute value">content</div></body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let attr: Option<String> = res.from_path("root.html_attr").and_then(|v| v.into());
+    assert_eq!(attr.unwrap(), "attribute value");
+    let html: Option<String> = res.from_path("root.html").and_then(|v| v.into());
+    assert_eq!(
+        html.unwrap(),
+        r#"<div html="attribute value">content</div>"#
+    );
+}
+
+#[test]
+fn decode_entities_in_attributes() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: raw
+      base_path: a[title]
+      extract: title
+    - name: decoded
+      base_path: a[title]
+      extract: title
+      decode_entities: true
+    - name: text
+      base_path: a[title]
+      extract: text
+      decode_entities: true
+  ";
+    let html =
+        r#"<html><body><a title="Fish &amp;amp; Chips">Fish &amp;amp; Chips</a></body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(html);
+
+    let raw: Option<String> = res.from_path("root.raw").and_then(|v| v.into());
+    assert_eq!(raw.unwrap(), "Fish &amp; Chips");
+    let decoded: Option<String> = res.from_path("root.decoded").and_then(|v| v.into());
+    assert_eq!(decoded.unwrap(), "Fish & Chips");
+    let text: Option<String> = res.from_path("root.text").and_then(|v| v.into());
+    assert_eq!(text.unwrap(), "Fish &amp; Chips");
+}
+
+#[test]
+fn with_total() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      with_total: total
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+  ";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(HTML_DOC);
+
+    let totals: Option<Vec<i64>> = res.from_path("root.results.#.total").and_then(|v| v.into());
+    assert_eq!(totals.unwrap(), vec![21; 21]);
+}
+
+#[test]
+fn parse_each_item() {
+    let cfg = Config::from_yaml(CFG_YAML).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let mut count = 0;
+    let mut first_url = None;
+    finder.parse_each(HTML_DOC, "results", |item| {
+        if count == 0 {
+            first_url = item.from_path("url");
+        }
+        count += 1;
+    });
+
+    let res = finder.parse(HTML_DOC);
+    let expected: Option<i64> = res.from_path("root.results.#").and_then(|v| v.into());
+    assert_eq!(count, expected.unwrap());
+    assert_eq!(first_url, res.from_path("root.results.0.url"));
+
+    let mut count = 0;
+    finder.parse_each(HTML_DOC, "missing", |_| count += 1);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn include_meta() {
+    let cfg_yaml: &str = r"
+  name: root
+  base_path: html
+  children:
+    - name: results
+      base_path: div.serp__results div.result
+      many: true
+      include_meta: true
+      children:
+        - name: url
+          base_path: h2.result__title > a[href]
+          extract: href
+  ";
+    let mut cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = finder.parse(HTML_DOC);
+
+    let tags: Option<Vec<String>> = res
+        .from_path("root.results.#._meta.tag")
+        .and_then(|v| v.into());
+    assert_eq!(tags.unwrap(), vec!["div"; 21]);
+    let class: Option<String> = res
+        .from_path("root.results.0._meta.class")
+        .and_then(|v| v.into());
+    assert!(class.unwrap().contains("result"));
+
+    cfg.children[0].meta_key = Some("node".to_string());
+    let finder = Finder::new(&cfg).unwrap();
+    let tag: Option<String> = finder
+        .parse(HTML_DOC)
+        .from_path("root.results.0.node.tag")
+        .and_then(|v| v.into());
+    assert_eq!(tag.unwrap(), "div");
+}
@@ -1,4 +1,8 @@
-use dom_finder::{Config, Finder};
+use std::panic::{self, RefUnwindSafe, UnwindSafe};
+use std::sync::Arc;
+use std::thread;
+
+use dom_finder::{Config, Finder, ParseError, Pipeline, PipelineError, Value};
 
 const CFG_YAML: &str = r"
 name: root
@@ -109,3 +113,347 @@ fn pipeline_extract_first_item() {
     let title: Option<String> = results.from_path("root.title").and_then(|s| s.into());
     assert_eq!(title.unwrap(), "A Brief List of Fruit Nutrition Facts");
 }
+
+#[test]
+fn pipeline_numbers_with_units() {
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: nutrients
+    base_path: table tr.nutrition-item
+    many: true
+    extract: text
+    pipeline: [ [normalize_spaces], [regex, '^\w+ \d+ calories (.+)$'], [numbers_with_units] ]
+";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let results = finder.parse(HTML_DOC);
+    let nutrients: Option<Vec<f64>> = results.from_path("root.nutrients.0").and_then(|v| v.into());
+    assert_eq!(nutrients.unwrap(), vec![10.3, 10.0]);
+}
+
+#[test]
+fn pipeline_parse_single_field() {
+    let cfg = Config::from_yaml(CFG_YAML).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let title: Option<String> = finder.parse_field(HTML_DOC, "title").into();
+    assert_eq!(title.unwrap(), "A Brief List of Fruit Nutrition Facts");
+
+    let calories: Option<Vec<i64>> = finder.parse_field(HTML_DOC, "results.calories").into();
+    let expected: Option<Vec<i64>> = finder
+        .parse(HTML_DOC)
+        .from_path("root.results.#.calories")
+        .and_then(|v| v.into());
+    assert_eq!(calories, expected);
+
+    assert_eq!(finder.parse_field(HTML_DOC, "missing"), Value::Null);
+}
+
+#[test]
+fn pipeline_check_shape() {
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: items
+    base_path: div[data-item]
+    many: true
+    extract: data-item
+    pipeline: [ [check_shape, 'object:{id:int,name:string}'], [extract_json, name] ]
+";
+    let html = r#"<html><body>
+    <div data-item='{"id": 1, "name": "Apple"}'></div>
+    <div data-item='{"id": "2", "name": "Banana"}'></div>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let items: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("root.items")
+        .and_then(|v| v.into());
+    assert_eq!(items.unwrap(), vec!["Apple", ""]);
+}
+
+#[test]
+fn pipeline_split() {
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: tags
+    base_path: div[data-tags]
+    extract: data-tags
+    pipeline: [ [split, ','] ]
+";
+    let html = r#"<html><body><div data-tags="a,b,c"></div></body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let tags: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("root.tags")
+        .and_then(|v| v.into());
+    assert_eq!(tags.unwrap(), vec!["a", "b", "c"]);
+}
+
+#[cfg(feature = "urls")]
+#[test]
+fn pipeline_resolve_url_with_document_base() {
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: links
+    base_path: a[href]
+    many: true
+    extract: href
+    pipeline: [ [resolve_url, ''] ]
+";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let html = r#"<html><head><base href="https://x.com/dir/"></head>
+    <body><a href="a">A</a><a href="/b">B</a></body></html>"#;
+    let links: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("root.links")
+        .and_then(|v| v.into());
+    assert_eq!(
+        links.unwrap(),
+        vec!["https://x.com/dir/a", "https://x.com/b"]
+    );
+
+    let html = r#"<html><body><a href="a">A</a></body></html>"#;
+    let links: Option<Vec<String>> = finder
+        .parse(html)
+        .from_path("root.links")
+        .and_then(|v| v.into());
+    assert_eq!(links.unwrap(), vec!["a"]);
+}
+
+#[test]
+fn pipeline_custom_proc() {
+    Pipeline::register(
+        "normalize_sku",
+        Arc::new(|v: &str| v.trim().trim_start_matches("SKU-").to_uppercase()),
+    );
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: skus
+    base_path: span.sku
+    many: true
+    extract: text
+    pipeline: [ [normalize_sku], [prepend, 'id:'] ]
+";
+    let html = r#"<html><body>
+    <span class="sku"> SKU-ab12 </span><span class="sku">cd34</span>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Arc::new(Finder::new(&cfg).unwrap());
+
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let finder = finder.clone();
+            thread::spawn(move || {
+                let skus: Option<Vec<String>> = finder
+                    .parse(html)
+                    .from_path("root.skus")
+                    .and_then(|v| v.into());
+                skus.unwrap()
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), vec!["id:AB12", "id:CD34"]);
+    }
+}
+
+#[test]
+fn finder_is_unwind_safe() {
+    fn assert_unwind_safe<T: RefUnwindSafe + UnwindSafe>() {}
+    assert_unwind_safe::<Finder<'static>>();
+    assert_unwind_safe::<Pipeline<'static>>();
+    assert_unwind_safe::<&Finder<'static>>();
+
+    let cfg = Config::from_yaml(
+        r"
+name: root
+base_path: h1
+extract: text
+",
+    )
+    .unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res = panic::catch_unwind(|| finder.parse("<html><body><h1>Title</h1></body></html>"));
+    assert_eq!(res.unwrap().from_path("root"), Some(Value::from("Title")));
+}
+
+#[test]
+fn pipeline_custom_proc_errors() {
+    let res = Pipeline::new(&vec![vec!["not_registered".to_string()]]);
+    assert!(matches!(
+        res,
+        Err(ParseError::Pipeline(PipelineError::ProcDoesNotExist(_)))
+    ));
+
+    Pipeline::register("with_args", Arc::new(|v: &str| v.to_string()));
+    let res = Pipeline::new(&vec![vec!["with_args".to_string(), "x".to_string()]]);
+    assert!(matches!(
+        res,
+        Err(ParseError::Pipeline(PipelineError::ProcInvalidArgument(
+            _,
+            _
+        )))
+    ));
+}
+
+#[test]
+fn pipeline_isolate_panics() {
+    Pipeline::register(
+        "panicking_proc",
+        Arc::new(|v: &str| panic!("cannot handle `{}`", v)),
+    );
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: title
+    base_path: h1
+    extract: text
+    pipeline: [ [normalize_spaces] ]
+  - name: broken
+    base_path: h1
+    extract: text
+    isolate_panics: true
+    pipeline: [ [panicking_proc] ]
+  - name: calories
+    base_path: table tr.nutrition-item td:nth-child(2)
+    extract: text
+    pipeline: [ [regex_find, '^(\d+)'] ]
+    cast: int
+";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let (res, warnings) = finder.parse_with_warnings(HTML_DOC);
+    let title: Option<String> = res.from_path("root.title").and_then(|v| v.into());
+    assert_eq!(title.unwrap(), "A Brief List of Fruit Nutrition Facts");
+    let calories: Option<i64> = res.from_path("root.calories").and_then(|v| v.into());
+    assert_eq!(calories.unwrap(), 52);
+    assert!(res.from_path("root.broken").is_none());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("`broken`: extraction panicked: cannot handle"));
+}
+
+#[test]
+fn pipeline_cast_json() {
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: nutrition
+    base_path: table tr.nutrition-item
+    many: true
+    extract: data-nutrition
+    cast: json
+  - name: product
+    base_path: div[data-product]
+    extract: data-product
+    cast: json
+  - name: broken
+    base_path: div[data-broken]
+    extract: data-broken
+    cast: json
+";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let results = finder.parse(HTML_DOC);
+    let carbs: Option<String> = results
+        .from_path("root.nutrition.0.carbs")
+        .and_then(|v| v.into());
+    assert_eq!(carbs.unwrap(), "14g");
+
+    let html = r#"<html><body>
+    <div data-product='{"name": "Apple", "price": {"amount": 1.5, "currency": "EUR"}, "tags": ["fruit"]}'></div>
+    <div data-broken='{"name": '></div>
+    </body></html>"#;
+    let results = finder.parse(html);
+    assert_eq!(
+        results.from_path("root.product.price.amount"),
+        Some(Value::Float(1.5))
+    );
+    let tags: Option<Vec<String>> = results
+        .from_path("root.product.tags")
+        .and_then(|v| v.into());
+    assert_eq!(tags.unwrap(), vec!["fruit"]);
+    assert!(results.from_path("root.broken").is_none());
+}
+
+#[test]
+fn pipeline_strict_cast() {
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: prices
+    base_path: span.price
+    many: true
+    extract: text
+    cast: int
+    strict_cast: true
+  - name: lenient_prices
+    base_path: span.price
+    many: true
+    extract: text
+    cast: int
+";
+    let html = r#"<html><body>
+    <span class="price">10</span><span class="price">abc</span><span class="price">0</span>
+    </body></html>"#;
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+
+    let results = finder.parse(html);
+    assert_eq!(
+        results.from_path("root.prices"),
+        Some(Value::Array(vec![
+            Value::Int(10),
+            Value::Null,
+            Value::Int(0)
+        ]))
+    );
+    assert_eq!(
+        results.from_path("root.lenient_prices"),
+        Some(Value::Array(vec![
+            Value::Int(10),
+            Value::Int(0),
+            Value::Int(0)
+        ]))
+    );
+}
+
+#[test]
+fn pipeline_sum_numbers_with_cast() {
+    let cfg_yaml: &str = r"
+name: root
+base_path: html
+children:
+  - name: total
+    base_path: p.basket
+    extract: text
+    pipeline: [ [sum_numbers] ]
+    cast: int
+";
+    let cfg = Config::from_yaml(cfg_yaml).unwrap();
+    let finder = Finder::new(&cfg).unwrap();
+    let res =
+        finder.parse(r#"<html><body><p class="basket">12 apples, 8 oranges</p></body></html>"#);
+    assert_eq!(res.from_path("root.total"), Some(Value::Int(20)));
+}